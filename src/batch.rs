@@ -0,0 +1,133 @@
+use crate::{EfChunk, EfVec};
+use std::collections::VecDeque;
+
+/// The default number of lookups that batched queries prefetch ahead.
+pub const DEFAULT_PREFETCH_DISTANCE: usize = 32;
+
+impl<C: EfChunk, E: AsRef<[C]>> EfVec<C, E> {
+    /// Look up the values at all `indices`, writing them to `out`.
+    ///
+    /// The cacheline for `indices[i + DEFAULT_PREFETCH_DISTANCE]` is
+    /// prefetched while `indices[i]` is looked up, so that many memory
+    /// accesses are in flight at the same time.
+    pub fn get_batch(&self, indices: &[usize], out: &mut [u64]) {
+        self.get_batch_with_distance(indices, out, DEFAULT_PREFETCH_DISTANCE);
+    }
+
+    /// Like [`Self::get_batch`], but prefetching `distance` lookups ahead.
+    pub fn get_batch_with_distance(&self, indices: &[usize], out: &mut [u64], distance: usize) {
+        assert_eq!(
+            indices.len(),
+            out.len(),
+            "indices and out must have the same length."
+        );
+        let distance = distance.min(indices.len());
+        for &index in &indices[..distance] {
+            self.prefetch(index);
+        }
+        for (i, (&index, v)) in indices.iter().zip(out.iter_mut()).enumerate() {
+            if let Some(&ahead) = indices.get(i.saturating_add(distance)) {
+                self.prefetch(ahead);
+            }
+            *v = self.index(index);
+        }
+    }
+
+    /// Iterate over the values at the given `indices`, prefetching
+    /// [`DEFAULT_PREFETCH_DISTANCE`] lookups ahead.
+    pub fn get_batch_iter<I: IntoIterator<Item = usize>>(
+        &self,
+        indices: I,
+    ) -> BatchIter<'_, C, E, I::IntoIter> {
+        self.get_batch_iter_with_distance(indices, DEFAULT_PREFETCH_DISTANCE)
+    }
+
+    /// Like [`Self::get_batch_iter`], but prefetching `distance` lookups ahead.
+    pub fn get_batch_iter_with_distance<I: IntoIterator<Item = usize>>(
+        &self,
+        indices: I,
+        distance: usize,
+    ) -> BatchIter<'_, C, E, I::IntoIter> {
+        let mut indices = indices.into_iter();
+        // The buffer grows with the number of indices actually read, since
+        // `distance` may be much larger than the number of indices.
+        let mut ahead = VecDeque::new();
+        for index in indices.by_ref().take(distance) {
+            self.prefetch(index);
+            ahead.push_back(index);
+        }
+        BatchIter {
+            ef: self,
+            indices,
+            ahead,
+        }
+    }
+}
+
+/// Iterator over the values at a sequence of indices, returned by [`EfVec::get_batch_iter`].
+pub struct BatchIter<'a, C, E, I> {
+    ef: &'a EfVec<C, E>,
+    indices: I,
+    /// Indices that have been prefetched but not yet returned.
+    ahead: VecDeque<usize>,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>, I: Iterator<Item = usize>> Iterator for BatchIter<'_, C, E, I> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if let Some(index) = self.indices.next() {
+            self.ef.prefetch(index);
+            self.ahead.push_back(index);
+        }
+        let index = self.ahead.pop_front()?;
+        Some(self.ef.index(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.indices.size_hint();
+        let n = self.ahead.len();
+        (lo.saturating_add(n), hi.and_then(|hi| hi.checked_add(n)))
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>, I: ExactSizeIterator<Item = usize>> ExactSizeIterator
+    for BatchIter<'_, C, E, I>
+{
+}
+
+#[test]
+fn get_batch() {
+    let vals: Vec<u64> = (0..100000).map(|i| i * 100 + i % 7).collect();
+    let ef = crate::CachelineEfVec::new(&vals);
+    let indices: Vec<usize> = (0..10000)
+        .map(|_| rand::random::<usize>() % vals.len())
+        .collect();
+    let expected: Vec<u64> = indices.iter().map(|&i| vals[i]).collect();
+
+    for distance in [
+        0,
+        1,
+        8,
+        DEFAULT_PREFETCH_DISTANCE,
+        100000,
+        usize::MAX / 2,
+        usize::MAX,
+    ] {
+        let mut out = vec![0; indices.len()];
+        ef.get_batch_with_distance(&indices, &mut out, distance);
+        assert_eq!(out, expected);
+        let out: Vec<u64> = ef
+            .get_batch_iter_with_distance(indices.iter().copied(), distance)
+            .collect();
+        assert_eq!(out, expected);
+    }
+    let mut out = vec![0; indices.len()];
+    ef.get_batch(&indices, &mut out);
+    assert_eq!(out, expected);
+    assert_eq!(
+        ef.get_batch_iter(indices.iter().copied()).len(),
+        indices.len()
+    );
+    assert_eq!(ef.get_batch_iter([]).next(), None);
+}
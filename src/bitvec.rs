@@ -0,0 +1,196 @@
+use crate::{first, CachelineEf, CachelineEfError, EfChunk, EfVec};
+
+/// A sparse bit vector whose one-bits are stored in [`CachelineEf`] chunks.
+pub type CachelineEfBitVec<E = Vec<CachelineEf>> = EfBitVec<CachelineEf, E>;
+
+/// A sparse bit vector of length `universe`, storing the positions of its
+/// one-bits in an [`EfVec`].
+///
+/// `select1` reads a single cacheline, and `rank1`, `get`, `rank0` and
+/// `select0` do a binary search over the first value of each chunk, followed
+/// by a search within a single cacheline. None of them allocate.
+#[derive(Clone, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+pub struct EfBitVec<C, E = Vec<C>> {
+    ones: EfVec<C, E>,
+    universe: u64,
+}
+
+impl<C: EfChunk> EfBitVec<C> {
+    /// Build a bit vector of length `universe` with one-bits at the given
+    /// positions.
+    ///
+    /// Panics when the positions are not strictly increasing or not less than
+    /// `universe`; see [`Self::try_new`].
+    pub fn new(ones: &[u64], universe: u64) -> Self {
+        Self::try_new(ones, universe).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Build a bit vector of length `universe` with one-bits at the given
+    /// positions, or return an error when the positions are not strictly
+    /// increasing or not less than `universe`.
+    pub fn try_new(ones: &[u64], universe: u64) -> Result<Self, CachelineEfError> {
+        for (i, w) in ones.windows(2).enumerate() {
+            if w[0] >= w[1] {
+                return Err(CachelineEfError::NotStrictlyIncreasing {
+                    chunk: (i + 1) / C::L,
+                    index: i + 1,
+                    prev: w[0],
+                    value: w[1],
+                });
+            }
+        }
+        if let Some(&last) = ones.last().filter(|&&last| last >= universe) {
+            return Err(CachelineEfError::ValueTooLarge {
+                chunk: (ones.len() - 1) / C::L,
+                value: last,
+                bound: universe,
+            });
+        }
+        Ok(Self {
+            ones: EfVec::new(ones),
+            universe,
+        })
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> EfBitVec<C, E> {
+    /// The length of the bit vector.
+    pub fn universe(&self) -> u64 {
+        self.universe
+    }
+
+    /// The number of one-bits.
+    pub fn count_ones(&self) -> usize {
+        self.ones.len()
+    }
+
+    /// The number of zero-bits.
+    pub fn count_zeros(&self) -> u64 {
+        self.universe - self.ones.len() as u64
+    }
+
+    /// The positions of the one-bits.
+    pub fn ones(&self) -> &EfVec<C, E> {
+        &self.ones
+    }
+
+    /// The value of the bit at position `pos`.
+    pub fn get(&self, pos: u64) -> bool {
+        assert!(
+            pos < self.universe,
+            "Position {pos} out of bounds. Universe is {}.",
+            self.universe
+        );
+        self.ones.next_geq(pos).is_some_and(|(_, v)| v == pos)
+    }
+
+    /// The position of the `k`'th one-bit.
+    pub fn select1(&self, k: usize) -> u64 {
+        self.ones.index(k)
+    }
+
+    /// The number of one-bits before position `pos`.
+    pub fn rank1(&self, pos: u64) -> usize {
+        self.ones.rank(pos)
+    }
+
+    /// The number of zero-bits before position `pos`.
+    pub fn rank0(&self, pos: u64) -> u64 {
+        assert!(
+            pos <= self.universe,
+            "Position {pos} out of bounds. Universe is {}.",
+            self.universe
+        );
+        pos - self.rank1(pos) as u64
+    }
+
+    /// The position of the `k`'th zero-bit.
+    pub fn select0(&self, k: u64) -> u64 {
+        assert!(
+            k < self.count_zeros(),
+            "Zero-bit {k} out of bounds. There are {} zero-bits.",
+            self.count_zeros()
+        );
+        // The number of zeros before the `i`'th one is `ones[i] - i`. The
+        // answer is `k` plus the number of ones with at most `k` zeros before them.
+        let chunks = self.ones.chunks();
+        let (mut lo, mut hi) = (0, chunks.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if first(&chunks[mid]) - (mid * C::L) as u64 <= k {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        if lo == 0 {
+            return k;
+        }
+        // Within the chunk, `ones[i] - i` is non-decreasing as well.
+        let chunk = lo - 1;
+        let start = chunk * C::L;
+        let (mut lo, mut hi) = (0, self.ones.chunk_len(chunk));
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.ones.chunk_get(chunk, mid) - ((start + mid) as u64) <= k {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        k + (start + lo) as u64
+    }
+}
+
+#[test]
+fn bitvec() {
+    for density in [2, 10, 100, 1000] {
+        let universe = 100000;
+        let ones: Vec<u64> = (0..universe)
+            .filter(|_| rand::random::<u64>().is_multiple_of(density))
+            .collect();
+        let bv = CachelineEfBitVec::new(&ones, universe);
+        let zeros: Vec<u64> = (0..universe)
+            .filter(|p| ones.binary_search(p).is_err())
+            .collect();
+        assert_eq!(bv.universe(), universe);
+        assert_eq!(bv.count_ones(), ones.len());
+        assert_eq!(bv.count_zeros(), zeros.len() as u64);
+
+        let mut rank1 = 0;
+        for pos in 0..universe {
+            assert_eq!(bv.rank1(pos), rank1);
+            assert_eq!(bv.rank0(pos), pos - rank1 as u64);
+            let bit = ones.get(rank1) == Some(&pos);
+            assert_eq!(bv.get(pos), bit);
+            rank1 += bit as usize;
+        }
+        for (k, &p) in ones.iter().enumerate() {
+            assert_eq!(bv.select1(k), p);
+        }
+        for (k, &p) in zeros.iter().enumerate() {
+            assert_eq!(bv.select0(k as u64), p, "k = {k}");
+        }
+    }
+
+    assert_eq!(
+        CachelineEfBitVec::try_new(&[1, 3, 3], 10).err(),
+        Some(CachelineEfError::NotStrictlyIncreasing {
+            chunk: 0,
+            index: 2,
+            prev: 3,
+            value: 3
+        })
+    );
+    assert_eq!(
+        CachelineEfBitVec::try_new(&[1, 3, 10], 10).err(),
+        Some(CachelineEfError::ValueTooLarge {
+            chunk: 0,
+            value: 10,
+            bound: 10
+        })
+    );
+    let bv = CachelineEfBitVec::new(&[1, 3], 10);
+    assert_eq!(bv.rank0(10), 8);
+}
@@ -0,0 +1,324 @@
+use crate::{escaped, first, from_words, words, CachelineEf, CachelineEfError, EfChunk, EfVec};
+use std::marker::PhantomData;
+
+/// Builder for a [`CachelineEfVec`](crate::CachelineEfVec).
+pub type CachelineEfVecBuilder = EfVecBuilder<CachelineEf>;
+
+/// Encodes a stream of non-decreasing values into an [`EfVec`], without
+/// first collecting them into a slice.
+///
+/// Values are buffered until a full chunk of [`EfChunk::L`] values is
+/// available, which is then encoded immediately.
+pub struct EfVecBuilder<C> {
+    chunks: Vec<C>,
+    /// Overflow cachelines of escaped chunks, appended to `chunks` by `finish`.
+    overflow: Vec<C>,
+    /// The values of the current, incomplete, chunk.
+    buf: Vec<u64>,
+    len: usize,
+    last: u64,
+}
+
+impl<C: EfChunk> Default for EfVecBuilder<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: EfChunk> EfVecBuilder<C> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a builder with space for `n` values.
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            chunks: Vec::with_capacity(n.div_ceil(C::L)),
+            overflow: vec![],
+            buf: Vec::with_capacity(C::L),
+            len: 0,
+            last: 0,
+        }
+    }
+
+    /// The number of values pushed so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append a value.
+    ///
+    /// Panics when `v` is smaller than the previous value; see [`Self::try_push`].
+    pub fn push(&mut self, v: u64) {
+        self.try_push(v).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Append a value, or return an error when it is smaller than the
+    /// previous value. In that case, the builder is left unchanged.
+    pub fn try_push(&mut self, v: u64) -> Result<(), CachelineEfError> {
+        if self.len > 0 && v < self.last {
+            return Err(CachelineEfError::NotSorted {
+                chunk: self.len / C::L,
+                index: self.len,
+                prev: self.last,
+                value: v,
+            });
+        }
+        self.buf.push(v);
+        self.len += 1;
+        self.last = v;
+        if self.buf.len() == C::L {
+            self.flush();
+        }
+        Ok(())
+    }
+
+    /// Encode the buffered values, or escape them when they do not fit.
+    fn flush(&mut self) {
+        let chunk = self.chunks.len();
+        self.chunks
+            .push(encode(&self.buf, chunk, &mut self.overflow));
+        self.buf.clear();
+    }
+
+    /// Encode the remaining values and return the final [`EfVec`].
+    pub fn finish(mut self) -> EfVec<C> {
+        if !self.buf.is_empty() {
+            self.flush();
+        }
+        self.chunks.append(&mut self.overflow);
+        EfVec {
+            ef: self.chunks,
+            len: self.len,
+            _chunk: PhantomData,
+        }
+    }
+}
+
+/// Encode chunk `chunk` with between 1 and [`EfChunk::L`] sorted values, or
+/// escape it by appending the values to the `overflow` cachelines.
+fn encode<C: EfChunk>(vals: &[u64], chunk: usize, overflow: &mut Vec<C>) -> C {
+    match C::try_new(vals, chunk) {
+        Ok(c) => c,
+        Err(CachelineEfError::RangeTooLarge { .. } | CachelineEfError::ValueTooLarge { .. }) => {
+            let c = from_words(&[0, overflow.len() as u64, vals[0]]);
+            overflow.extend(vals.chunks(words::<C>()).map(from_words::<C>));
+            c
+        }
+        Err(e) => unreachable!("{e}"),
+    }
+}
+
+impl<C: EfChunk> EfVec<C, Vec<C>> {
+    /// Append a value, re-encoding the last chunk when it is not full.
+    ///
+    /// Panics when `v` is smaller than the last value; see [`Self::try_push`].
+    pub fn push(&mut self, v: u64) {
+        self.try_push(v).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Append a value, or return an error when it is smaller than the last
+    /// value. In that case, the vector is left unchanged.
+    pub fn try_push(&mut self, v: u64) -> Result<(), CachelineEfError> {
+        self.try_extend_from_slice(&[v])
+    }
+
+    /// Append non-decreasing values.
+    ///
+    /// Panics when the values are not sorted; see [`Self::try_extend_from_slice`].
+    pub fn extend_from_slice(&mut self, vals: &[u64]) {
+        self.try_extend_from_slice(vals)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Append non-decreasing values, or return an error explaining where the
+    /// values are not sorted. In that case, the vector is left unchanged.
+    ///
+    /// Only the last chunk is re-encoded when it is not full; new values are
+    /// encoded into new chunks.
+    pub fn try_extend_from_slice(&mut self, vals: &[u64]) -> Result<(), CachelineEfError> {
+        let mut prev = if self.is_empty() {
+            0
+        } else {
+            self.index(self.len - 1)
+        };
+        for (i, &v) in vals.iter().enumerate() {
+            if v < prev {
+                let index = self.len + i;
+                return Err(CachelineEfError::NotSorted {
+                    chunk: index / C::L,
+                    index,
+                    prev,
+                    value: v,
+                });
+            }
+            prev = v;
+        }
+        if !vals.is_empty() {
+            self.append_sorted(vals.iter().copied());
+        }
+        Ok(())
+    }
+
+    /// Append values that are sorted and not smaller than the last value.
+    ///
+    /// The partial last chunk is re-encoded and overwritten in place, and the
+    /// new chunks are inserted before the overflow cachelines at once.
+    pub(crate) fn append_sorted(&mut self, vals: impl IntoIterator<Item = u64>) {
+        let num_chunks = self.len.div_ceil(C::L);
+        let partial = !self.len.is_multiple_of(C::L);
+        let mut buf = Vec::with_capacity(C::L);
+        if partial {
+            self.chunk_decode(num_chunks - 1, &mut buf);
+            // Drop the overflow cachelines of the last chunk when they are the
+            // last ones, as is the case when the vector was built by appending.
+            if let Some(block) = escaped(&self.ef[num_chunks - 1]) {
+                let end = num_chunks + block + buf.len().div_ceil(words::<C>());
+                if end == self.ef.len() {
+                    self.ef.truncate(num_chunks + block);
+                }
+            }
+            self.len -= buf.len();
+        }
+
+        let first_chunk = self.len / C::L;
+        let mut chunks = vec![];
+        let mut overflow = vec![];
+        let mut vals = vals.into_iter();
+        loop {
+            buf.extend(vals.by_ref().take(C::L - buf.len()));
+            if buf.is_empty() {
+                break;
+            }
+            let chunk = first_chunk + chunks.len();
+            chunks.push(encode(&buf, chunk, &mut overflow));
+            self.len += buf.len();
+            let full = buf.len() == C::L;
+            buf.clear();
+            if !full {
+                break;
+            }
+        }
+        self.insert_chunks(first_chunk, num_chunks, chunks, overflow);
+    }
+
+    /// Overwrite chunks `first_chunk..num_chunks` and insert the remaining
+    /// `chunks` after them, where `num_chunks` is the current number of
+    /// chunks. The escaped `chunks` point into `overflow`, which is appended.
+    pub(crate) fn insert_chunks(
+        &mut self,
+        first_chunk: usize,
+        num_chunks: usize,
+        chunks: Vec<C>,
+        overflow: Vec<C>,
+    ) {
+        // Overflow blocks are relative to the end of the chunks, and the new
+        // overflow cachelines are placed after the existing ones.
+        let base = (self.ef.len() - num_chunks) as u64;
+        let mut chunks = chunks.into_iter().map(|c| match escaped(&c) {
+            Some(block) => from_words(&[0, base + block as u64, first(&c)]),
+            None => c,
+        });
+        for k in first_chunk..num_chunks {
+            self.ef[k] = chunks.next().unwrap();
+        }
+        if chunks.len() > 0 {
+            self.ef.splice(num_chunks..num_chunks, chunks);
+        }
+        self.ef.extend(overflow);
+    }
+}
+
+/// Panics when the values are not sorted.
+impl<C: EfChunk> Extend<u64> for EfVecBuilder<C> {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) {
+        for v in iter {
+            self.push(v);
+        }
+    }
+}
+
+/// Panics when the values are not sorted.
+impl<C: EfChunk> FromIterator<u64> for EfVec<C> {
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Self {
+        let mut builder = EfVecBuilder::new();
+        builder.extend(iter);
+        builder.finish()
+    }
+}
+
+#[test]
+fn builder() {
+    let vals = crate::test_vals(10000, 1000, 100);
+    let ef: crate::CachelineEfVec = vals.iter().copied().collect();
+    assert_eq!(ef.len(), vals.len());
+    assert!(ef.iter().eq(vals.iter().copied()));
+    assert_eq!(
+        ef.size_in_bytes(),
+        crate::CachelineEfVec::new(&vals).size_in_bytes()
+    );
+
+    let mut builder = CachelineEfVecBuilder::new();
+    builder.extend(vals[..100].iter().copied());
+    assert_eq!(
+        builder.try_push(vals[99] - 1),
+        Err(CachelineEfError::NotSorted {
+            chunk: 2,
+            index: 100,
+            prev: vals[99],
+            value: vals[99] - 1
+        })
+    );
+    builder.push(vals[99]);
+    let ef = builder.finish();
+    assert_eq!(ef.len(), 101);
+    assert_eq!(ef.index(100), vals[99]);
+
+    let ef: crate::PackedEfVec<6> = (0..1000).map(|i| i * 50).collect();
+    assert!(ef.iter().eq((0..1000).map(|i| i * 50)));
+    assert!(CachelineEfVecBuilder::new().finish().is_empty());
+}
+
+#[test]
+fn push() {
+    let vals = crate::test_vals(3000, 300, 100);
+    let mut ef = crate::CachelineEfVec::default();
+    for &v in &vals {
+        ef.push(v);
+    }
+    assert!(ef.iter().eq(vals.iter().copied()));
+    assert_eq!(ef.validate(), Ok(()));
+    assert_eq!(
+        ef.size_in_bytes(),
+        crate::CachelineEfVec::new(&vals).size_in_bytes()
+    );
+
+    let mut ef = crate::CachelineEfVec::new(&vals[..10]);
+    let mut i = 10;
+    while i < vals.len() {
+        let j = (i + rand::random::<usize>() % 100).min(vals.len());
+        ef.extend_from_slice(&vals[i..j]);
+        i = j;
+    }
+    assert!(ef.iter().eq(vals.iter().copied()));
+    assert_eq!(ef.validate(), Ok(()));
+
+    let len = ef.len();
+    assert_eq!(
+        ef.try_extend_from_slice(&[u64::MAX, 0]),
+        Err(CachelineEfError::NotSorted {
+            chunk: (len + 1) / 44,
+            index: len + 1,
+            prev: u64::MAX,
+            value: 0
+        })
+    );
+    assert_eq!(ef.len(), len);
+    assert!(ef.try_push(vals[len - 1] - 1).is_err());
+    ef.push(u64::MAX);
+    assert_eq!(ef.index(len), u64::MAX);
+}
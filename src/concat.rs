@@ -0,0 +1,126 @@
+use crate::{escaped, first, from_words, words, CachelineEfError, EfChunk, EfVec};
+
+impl<C: EfChunk> EfVec<C, Vec<C>> {
+    /// Concatenate vectors, or return an error when the last value of a part
+    /// is larger than the first value of the next.
+    ///
+    /// Full chunks are copied as-is when they start at a multiple of
+    /// [`EfChunk::L`] in the result, i.e. when all preceding parts have a
+    /// length that is a multiple of `L`. Other values are re-encoded.
+    pub fn concat<E: AsRef<[C]>>(parts: &[EfVec<C, E>]) -> Result<Self, CachelineEfError> {
+        let mut ef = Self::default();
+        let mut last = None;
+        for part in parts {
+            if part.is_empty() {
+                continue;
+            }
+            let value = part.index(0);
+            if let Some(prev) = last.filter(|&prev| prev > value) {
+                return Err(CachelineEfError::NotSorted {
+                    chunk: ef.len / C::L,
+                    index: ef.len,
+                    prev,
+                    value,
+                });
+            }
+            last = Some(part.index(part.len - 1));
+            ef.append_range(part, 0, part.len);
+        }
+        Ok(ef)
+    }
+
+    /// Append the values at positions `start..end` of `other`, which must not
+    /// be smaller than the last value of `self`.
+    fn append_range<E: AsRef<[C]>>(&mut self, other: &EfVec<C, E>, start: usize, end: usize) {
+        let mut start = start;
+        if self.len.is_multiple_of(C::L) && start.is_multiple_of(C::L) && end / C::L > start / C::L
+        {
+            // Copy full chunks, and the overflow cachelines of escaped chunks.
+            let other_chunks = other.chunks();
+            let mut chunks = Vec::with_capacity(end / C::L - start / C::L);
+            let mut overflow = vec![];
+            for k in start / C::L..end / C::L {
+                let c = &other_chunks[k];
+                match escaped(c) {
+                    None => chunks.push(*c),
+                    Some(block) => {
+                        let block = other_chunks.len() + block;
+                        chunks.push(from_words(&[0, overflow.len() as u64, first(c)]));
+                        overflow.extend_from_slice(
+                            &other.ef.as_ref()[block..block + C::L.div_ceil(words::<C>())],
+                        );
+                    }
+                }
+            }
+            let num_chunks = self.len / C::L;
+            self.insert_chunks(num_chunks, num_chunks, chunks, overflow);
+            self.len += (end / C::L - start / C::L) * C::L;
+            start = end / C::L * C::L;
+        }
+        // Re-encode the remaining values in a single pass.
+        if start < end {
+            self.append_sorted(other.iter_from(start).take(end - start));
+        }
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> EfVec<C, E> {
+    /// Split into the first `index` values and the remaining values.
+    ///
+    /// Full chunks before `index` are copied as-is, as are full chunks after
+    /// `index` when `index` is a multiple of [`EfChunk::L`]. Other values are
+    /// re-encoded.
+    pub fn split_at(&self, index: usize) -> (EfVec<C>, EfVec<C>) {
+        assert!(
+            index <= self.len,
+            "Index {index} out of bounds. Length is {}.",
+            self.len
+        );
+        let mut left = EfVec::default();
+        left.append_range(self, 0, index);
+        let mut right = EfVec::default();
+        right.append_range(self, index, self.len);
+        (left, right)
+    }
+}
+
+#[test]
+fn concat_and_split() {
+    let vals = crate::test_vals(5000, 500, 100);
+    let ef = crate::CachelineEfVec::new(&vals);
+    for index in [0, 1, 43, 44, 45, 440, 2000, 4999, 5000] {
+        let (left, right) = ef.split_at(index);
+        assert!(left.iter().eq(vals[..index].iter().copied()));
+        assert!(right.iter().eq(vals[index..].iter().copied()));
+        assert_eq!(left.validate(), Ok(()));
+        assert_eq!(right.validate(), Ok(()));
+
+        let ef2 = crate::CachelineEfVec::concat(&[left, right]).unwrap();
+        assert!(ef2.iter().eq(vals.iter().copied()));
+        assert_eq!(ef2.validate(), Ok(()));
+        if index % 44 == 0 {
+            // All chunks are reused.
+            assert_eq!(ef2.size_in_bytes(), ef.size_in_bytes());
+        }
+    }
+
+    let parts: Vec<_> = vals.chunks(300).map(crate::CachelineEfVec::new).collect();
+    let ef2 = crate::CachelineEfVec::concat(&parts).unwrap();
+    assert!(ef2.iter().eq(vals.iter().copied()));
+    assert_eq!(ef2.validate(), Ok(()));
+
+    let parts = [
+        crate::CachelineEfVec::new(&[1, 5]),
+        crate::CachelineEfVec::new(&[]),
+        crate::CachelineEfVec::new(&[4]),
+    ];
+    assert_eq!(
+        crate::CachelineEfVec::concat(&parts).err(),
+        Some(CachelineEfError::NotSorted {
+            chunk: 0,
+            index: 2,
+            prev: 5,
+            value: 4
+        })
+    );
+}
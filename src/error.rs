@@ -0,0 +1,220 @@
+use std::fmt;
+
+/// Reasons why a list of values can not be encoded, or why encoded data is invalid.
+///
+/// Most variants carry the index of the chunk (of 44 consecutive values) that
+/// failed, together with the offending values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CachelineEfError {
+    /// The chunk does not contain any values.
+    EmptyChunk { chunk: usize },
+    /// The chunk is given `len` values, more than the `max` it can hold.
+    TooManyValues {
+        chunk: usize,
+        len: usize,
+        max: usize,
+    },
+    /// The value at position `index` is smaller than the value before it.
+    NotSorted {
+        chunk: usize,
+        index: usize,
+        prev: u64,
+        value: u64,
+    },
+    /// The value at position `index` is not larger than the value before it,
+    /// while values must be strictly increasing.
+    NotStrictlyIncreasing {
+        chunk: usize,
+        index: usize,
+        prev: u64,
+        value: u64,
+    },
+    /// The values in the chunk span a range larger than `max_range`.
+    RangeTooLarge {
+        chunk: usize,
+        first: u64,
+        last: u64,
+        max_range: u64,
+    },
+    /// The value does not fit in the number of bits supported by the encoding.
+    ValueTooLarge {
+        chunk: usize,
+        value: u64,
+        bound: u64,
+    },
+    /// The chunk encodes `found` values instead of `expected`.
+    WrongLength {
+        chunk: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The first value of the chunk does not correspond to bit 0 of the high part.
+    MissingFirstBit { chunk: usize },
+    /// The last value of the chunk does not fit in a `u64`.
+    DecodeOverflow { chunk: usize },
+    /// The first value stored in the header of an escaped chunk differs from
+    /// its first overflow value.
+    WrongFirstValue {
+        chunk: usize,
+        expected: u64,
+        found: u64,
+    },
+    /// The first offset of an [`EfOffsets`](crate::EfOffsets) chunk differs
+    /// from the last offset of the previous chunk, which should duplicate it.
+    OffsetMismatch {
+        chunk: usize,
+        expected: u64,
+        found: u64,
+    },
+    /// The overflow cachelines of an escaped chunk, starting at `block`, are
+    /// not within the `num_blocks` overflow cachelines.
+    EscapeOutOfBounds {
+        chunk: usize,
+        block: usize,
+        num_blocks: usize,
+    },
+    /// There are fewer than the `expected` chunks needed for `len` values.
+    TooFewChunks {
+        len: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The end offset `end` of the last string lies past the end of the
+    /// `bytes` bytes of an [`EfStrings`](crate::EfStrings).
+    OffsetOutOfBounds { end: u64, bytes: u64 },
+    /// The data is not aligned to `align` bytes.
+    Misaligned { align: usize },
+    /// The data has a size of `size` bytes, which is not a multiple of `chunk_size`.
+    InvalidSize { size: usize, chunk_size: usize },
+}
+
+impl CachelineEfError {
+    /// The index of the chunk that failed to encode or is invalid, if the
+    /// error concerns a single chunk.
+    pub fn chunk(&self) -> Option<usize> {
+        match *self {
+            CachelineEfError::EmptyChunk { chunk }
+            | CachelineEfError::TooManyValues { chunk, .. }
+            | CachelineEfError::NotSorted { chunk, .. }
+            | CachelineEfError::NotStrictlyIncreasing { chunk, .. }
+            | CachelineEfError::RangeTooLarge { chunk, .. }
+            | CachelineEfError::ValueTooLarge { chunk, .. }
+            | CachelineEfError::WrongLength { chunk, .. }
+            | CachelineEfError::MissingFirstBit { chunk }
+            | CachelineEfError::DecodeOverflow { chunk }
+            | CachelineEfError::WrongFirstValue { chunk, .. }
+            | CachelineEfError::OffsetMismatch { chunk, .. }
+            | CachelineEfError::EscapeOutOfBounds { chunk, .. } => Some(chunk),
+            CachelineEfError::TooFewChunks { .. }
+            | CachelineEfError::OffsetOutOfBounds { .. }
+            | CachelineEfError::Misaligned { .. }
+            | CachelineEfError::InvalidSize { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for CachelineEfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CachelineEfError::EmptyChunk { chunk } => {
+                write!(f, "Chunk {chunk}: list of values must not be empty.")
+            }
+            CachelineEfError::TooManyValues { chunk, len, max } => write!(
+                f,
+                "Chunk {chunk}: number of values must be at most {max}, but is {len}."
+            ),
+            CachelineEfError::NotSorted {
+                chunk,
+                index,
+                prev,
+                value,
+            } => write!(
+                f,
+                "Chunk {chunk}: values must be non-decreasing, but value {value} at index {index} is smaller than the previous value {prev}."
+            ),
+            CachelineEfError::NotStrictlyIncreasing {
+                chunk,
+                index,
+                prev,
+                value,
+            } => write!(
+                f,
+                "Chunk {chunk}: values must be strictly increasing, but value {value} at index {index} is not larger than the previous value {prev}."
+            ),
+            CachelineEfError::RangeTooLarge {
+                chunk,
+                first,
+                last,
+                max_range,
+            } => write!(
+                f,
+                "Chunk {chunk}: range of values {} ({first} to {last}) is too large! Can be at most {max_range}.",
+                last - first
+            ),
+            CachelineEfError::ValueTooLarge { chunk, value, bound } => write!(
+                f,
+                "Chunk {chunk}: value {value} is too large! Must be less than {bound}."
+            ),
+            CachelineEfError::WrongLength {
+                chunk,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Chunk {chunk}: expected {expected} values, but the chunk encodes {found}."
+            ),
+            CachelineEfError::MissingFirstBit { chunk } => write!(
+                f,
+                "Chunk {chunk}: bit 0 of the high part must be set for the first value."
+            ),
+            CachelineEfError::DecodeOverflow { chunk } => {
+                write!(f, "Chunk {chunk}: the last value does not fit in a u64.")
+            }
+            CachelineEfError::WrongFirstValue {
+                chunk,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Chunk {chunk}: escaped chunk stores first value {found}, but its first overflow value is {expected}."
+            ),
+            CachelineEfError::OffsetMismatch {
+                chunk,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Chunk {chunk}: first offset {found} differs from the last offset {expected} of the previous chunk."
+            ),
+            CachelineEfError::EscapeOutOfBounds {
+                chunk,
+                block,
+                num_blocks,
+            } => write!(
+                f,
+                "Chunk {chunk}: escaped values starting at overflow cacheline {block} are out of bounds; there are {num_blocks} overflow cachelines."
+            ),
+            CachelineEfError::TooFewChunks {
+                len,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{len} values need at least {expected} chunks, but there are only {found}."
+            ),
+            CachelineEfError::OffsetOutOfBounds { end, bytes } => write!(
+                f,
+                "Offset {end} lies past the end of the {bytes} bytes of the strings."
+            ),
+            CachelineEfError::Misaligned { align } => {
+                write!(f, "Data must be aligned to {align} bytes.")
+            }
+            CachelineEfError::InvalidSize { size, chunk_size } => write!(
+                f,
+                "Size of {size} bytes is not a multiple of the chunk size of {chunk_size} bytes."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CachelineEfError {}
@@ -0,0 +1,131 @@
+use crate::{words, CachelineEfError, EfChunk, EfVec};
+
+/// The predicted size of an [`EfVec`], returned by [`EfVec::estimate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpaceEstimate {
+    /// The number of values.
+    pub len: usize,
+    /// The number of chunks, excluding overflow cachelines.
+    pub chunks: usize,
+    /// The number of chunks that can not be encoded and would be escaped.
+    pub escaped_chunks: usize,
+    /// The size of the encoding in bytes, including overflow cachelines.
+    pub bytes: usize,
+    /// The size of plain Elias-Fano encoding in bytes, without select structures.
+    pub elias_fano_bytes: usize,
+}
+
+impl SpaceEstimate {
+    /// Whether all chunks can be encoded without escaping.
+    pub fn fits(&self) -> bool {
+        self.escaped_chunks == 0
+    }
+
+    /// The number of bits per value, or 0 for an empty list.
+    pub fn bits_per_value(&self) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        (8 * self.bytes) as f64 / self.len as f64
+    }
+
+    /// The number of bits per value of plain Elias-Fano, or 0 for an empty list.
+    pub fn elias_fano_bits_per_value(&self) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        (8 * self.elias_fano_bytes) as f64 / self.len as f64
+    }
+}
+
+impl<C: EfChunk> EfVec<C> {
+    /// Predict the size of the encoding of `vals`, without building it.
+    ///
+    /// Returns an error when the values are not sorted.
+    pub fn estimate(vals: &[u64]) -> Result<SpaceEstimate, CachelineEfError> {
+        let mut escaped_chunks = 0;
+        let mut overflow = 0;
+        let mut prev = 0;
+        for (k, chunk) in vals.chunks(C::L).enumerate() {
+            for (i, &v) in chunk.iter().enumerate() {
+                if v < prev {
+                    return Err(CachelineEfError::NotSorted {
+                        chunk: k,
+                        index: k * C::L + i,
+                        prev,
+                        value: v,
+                    });
+                }
+                prev = v;
+            }
+            if C::try_new(chunk, k).is_err() {
+                escaped_chunks += 1;
+                overflow += chunk.len().div_ceil(words::<C>());
+            }
+        }
+        let chunks = vals.len().div_ceil(C::L);
+        Ok(SpaceEstimate {
+            len: vals.len(),
+            chunks,
+            escaped_chunks,
+            bytes: (chunks + overflow) * size_of::<C>(),
+            elias_fano_bytes: elias_fano_bits(
+                vals.len(),
+                vals.last().map_or(0, |&v| v.saturating_add(1)),
+            )
+            .div_ceil(8),
+        })
+    }
+
+    /// Whether `vals` are sorted and can be encoded without escaping any chunk,
+    /// so that every query reads a single cacheline.
+    pub fn fits(vals: &[u64]) -> bool {
+        Self::estimate(vals).is_ok_and(|e| e.fits())
+    }
+}
+
+/// The number of bits of Elias-Fano encoding of `n` values less than `u`,
+/// using `max(0, floor(log2(u/n)))` low bits per value.
+fn elias_fano_bits(n: usize, u: u64) -> usize {
+    if n == 0 {
+        return 0;
+    }
+    let l = (u / n as u64).checked_ilog2().unwrap_or(0);
+    n * l as usize + n + (u >> l) as usize + 1
+}
+
+#[test]
+fn estimate() {
+    for max_gap in [10, 100, 200, 1000] {
+        let vals = crate::random_vals(10000, 0, max_gap);
+        let e = crate::CachelineEfVec::estimate(&vals).unwrap();
+        assert_eq!(e.len, vals.len());
+        assert_eq!(e.bytes, crate::CachelineEfVec::new(&vals).size_in_bytes());
+        assert_eq!(e.fits(), crate::CachelineEfVec::fits(&vals));
+        // Elias-Fano uses around `2 + log2(max_gap / 2)` bits per value.
+        let ef_bits = e.elias_fano_bits_per_value();
+        assert!(ef_bits > 2.0 && ef_bits < 3.0 + (max_gap as f64 / 2.0).log2());
+        if max_gap <= 100 {
+            assert!(e.fits());
+            assert!(e.bits_per_value() < 12.0);
+        }
+        if max_gap >= 1000 {
+            assert!(!e.fits());
+            assert!(e.escaped_chunks > 0);
+        }
+    }
+    assert_eq!(
+        crate::CachelineEfVec::estimate(&[3, 2]).err(),
+        Some(CachelineEfError::NotSorted {
+            chunk: 0,
+            index: 1,
+            prev: 3,
+            value: 2
+        })
+    );
+    assert!(!crate::CachelineEfVec::fits(&[3, 2]));
+    assert!(crate::CachelineEfVec::fits(&[]));
+    let e = crate::CachelineEfVec::estimate(&[]).unwrap();
+    assert_eq!(e.bits_per_value(), 0.0);
+    assert_eq!(e.elias_fano_bits_per_value(), 0.0);
+}
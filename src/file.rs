@@ -0,0 +1,310 @@
+//! A native file format for [`EfVec`], independent of `epserde`.
+//!
+//! A file consists of a 64-byte header, zero-padded to the alignment of the
+//! chunks when that is larger, followed by the raw chunks (including
+//! overflow cachelines), so that the chunks of a memory-mapped file are
+//! aligned. The header contains, as little-endian integers:
+//!
+//! | bytes  | field                                        |
+//! |--------|----------------------------------------------|
+//! | 0..8   | magic `CLEFVEC\0`                            |
+//! | 8..12  | format version, currently 1                  |
+//! | 12..16 | size of a chunk in bytes                     |
+//! | 16..24 | number of values per chunk, [`EfChunk::L`]   |
+//! | 24..32 | number of values                             |
+//! | 32..40 | number of chunks, including overflow         |
+//! | 40..48 | flags; bit 0 is set for big-endian chunks    |
+//! | 48..56 | FNV-1a checksum over the 64-bit chunk words  |
+//! | 56..64 | chunk type, [`EfChunk::TAG`]                 |
+//!
+//! Chunks are stored in native byte order.
+
+use crate::{from_words, EfChunk, EfVec};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    marker::PhantomData,
+    path::Path,
+};
+
+const MAGIC: [u8; 8] = *b"CLEFVEC\0";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 64;
+const FLAG_BIG_ENDIAN: u64 = 1;
+
+#[cfg(target_endian = "little")]
+const FLAGS: u64 = 0;
+#[cfg(target_endian = "big")]
+const FLAGS: u64 = FLAG_BIG_ENDIAN;
+
+struct Header {
+    len: usize,
+    num_chunks: usize,
+    checksum: u64,
+}
+
+impl Header {
+    fn to_bytes<C: EfChunk>(&self) -> [u8; HEADER_SIZE] {
+        let mut b = [0u8; HEADER_SIZE];
+        b[0..8].copy_from_slice(&MAGIC);
+        b[8..12].copy_from_slice(&VERSION.to_le_bytes());
+        b[12..16].copy_from_slice(&(size_of::<C>() as u32).to_le_bytes());
+        b[16..24].copy_from_slice(&(C::L as u64).to_le_bytes());
+        b[24..32].copy_from_slice(&(self.len as u64).to_le_bytes());
+        b[32..40].copy_from_slice(&(self.num_chunks as u64).to_le_bytes());
+        b[40..48].copy_from_slice(&FLAGS.to_le_bytes());
+        b[48..56].copy_from_slice(&self.checksum.to_le_bytes());
+        b[56..64].copy_from_slice(&C::TAG.to_le_bytes());
+        b
+    }
+
+    /// Parse a header, checking that it describes chunks of type `C`.
+    fn parse<C: EfChunk>(b: &[u8; HEADER_SIZE]) -> io::Result<Self> {
+        let u32_at = |i: usize| u32::from_le_bytes(b[i..i + 4].try_into().unwrap());
+        let u64_at = |i: usize| u64::from_le_bytes(b[i..i + 8].try_into().unwrap());
+        if b[0..8] != MAGIC {
+            return Err(invalid("Not a CachelineEf file: wrong magic".to_string()));
+        }
+        if u32_at(8) != VERSION {
+            return Err(invalid(format!(
+                "Unsupported format version {}, expected {VERSION}",
+                u32_at(8)
+            )));
+        }
+        if u32_at(12) as usize != size_of::<C>() || u64_at(16) != C::L as u64 {
+            return Err(invalid(format!(
+                "File contains chunks of {} bytes with {} values, expected {} bytes with {} values",
+                u32_at(12),
+                u64_at(16),
+                size_of::<C>(),
+                C::L
+            )));
+        }
+        // Different chunk types can have the same size and `L`.
+        if u64_at(56) != C::TAG {
+            return Err(invalid(format!(
+                "File contains chunks of type {:#x}, expected {:#x}",
+                u64_at(56),
+                C::TAG
+            )));
+        }
+        let flags = u64_at(40);
+        if flags & !FLAG_BIG_ENDIAN != 0 {
+            return Err(invalid(format!("Unknown flags {flags:#x}")));
+        }
+        if flags != FLAGS {
+            return Err(invalid(
+                "File was written with a different endianness".to_string(),
+            ));
+        }
+        let len = usize::try_from(u64_at(24)).map_err(|e| invalid(e.to_string()))?;
+        let num_chunks = usize::try_from(u64_at(32)).map_err(|e| invalid(e.to_string()))?;
+        if num_chunks < len.div_ceil(C::L) {
+            return Err(invalid(format!(
+                "Expected at least {} chunks for {len} values, found {num_chunks}",
+                len.div_ceil(C::L)
+            )));
+        }
+        Ok(Self {
+            len,
+            num_chunks,
+            checksum: u64_at(48),
+        })
+    }
+
+    /// The expected file size.
+    fn file_size<C>(&self) -> Option<u64> {
+        (self.num_chunks as u64)
+            .checked_mul(size_of::<C>() as u64)?
+            .checked_add(data_offset::<C>() as u64)
+    }
+}
+
+/// The position of the first chunk in the file: the header, padded to the
+/// alignment of the chunks.
+fn data_offset<C>() -> usize {
+    HEADER_SIZE.max(align_of::<C>())
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// FNV-1a, applied to 64-bit words instead of bytes.
+fn checksum<C: EfChunk>(ef: &[C]) -> u64 {
+    // SAFETY: `EfChunk` guarantees that chunks consist of plain words.
+    let words =
+        unsafe { std::slice::from_raw_parts(ef.as_ptr() as *const u64, size_of_val(ef) / 8) };
+    words.iter().fold(0xcbf29ce484222325, |h, &w| {
+        (h ^ w).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> EfVec<C, E> {
+    /// Write this vector to `path` in the native file format; see [`EfVec::load`].
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let ef = self.ef.as_ref();
+        let header = Header {
+            len: self.len,
+            num_chunks: ef.len(),
+            checksum: checksum(ef),
+        };
+        let mut w = BufWriter::new(File::create(path)?);
+        w.write_all(&header.to_bytes::<C>())?;
+        w.write_all(&vec![0; data_offset::<C>() - HEADER_SIZE])?;
+        w.write_all(self.as_bytes())?;
+        w.flush()
+    }
+}
+
+impl<C: EfChunk> EfVec<C> {
+    /// Read a vector written by [`EfVec::save`].
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error when the file was written
+    /// for a different chunk type or platform, or when the checksum or the
+    /// layout of the chunks is wrong.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut b = [0u8; HEADER_SIZE];
+        file.read_exact(&mut b)?;
+        let header = Header::parse::<C>(&b)?;
+        // Check the size before allocating, in case the header is corrupted.
+        if Some(file.metadata()?.len()) != header.file_size::<C>() {
+            return Err(invalid("File size does not match header".to_string()));
+        }
+        file.read_exact(&mut vec![0; data_offset::<C>() - HEADER_SIZE])?;
+        let mut ef = vec![from_words::<C>(&[]); header.num_chunks];
+        // SAFETY: `EfChunk` guarantees that any bit pattern is valid.
+        file.read_exact(unsafe {
+            std::slice::from_raw_parts_mut(ef.as_mut_ptr() as *mut u8, size_of_val(&ef[..]))
+        })?;
+        if checksum(&ef) != header.checksum {
+            return Err(invalid("Checksum mismatch".to_string()));
+        }
+        let ef = Self {
+            ef,
+            len: header.len,
+            _chunk: PhantomData,
+        };
+        ef.validate().map_err(|e| invalid(e.to_string()))?;
+        Ok(ef)
+    }
+}
+
+/// The chunks of a memory-mapped file, returned by [`EfVec::mmap`].
+#[cfg(feature = "mmap")]
+pub struct Mmapped<C> {
+    mmap: memmap2::Mmap,
+    num_chunks: usize,
+    _chunk: PhantomData<C>,
+}
+
+#[cfg(feature = "mmap")]
+impl<C: EfChunk> AsRef<[C]> for Mmapped<C> {
+    fn as_ref(&self) -> &[C] {
+        // SAFETY: `EfVec::mmap` checked the size and alignment of the chunks.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.mmap.as_ptr().add(data_offset::<C>()) as *const C,
+                self.num_chunks,
+            )
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<C: EfChunk> EfVec<C, Mmapped<C>> {
+    /// Memory-map a file written by [`EfVec::save`], without copying the chunks.
+    ///
+    /// Only the header and the file size are checked, so that opening is
+    /// instant regardless of the size of the file. Call [`EfVec::validate`]
+    /// on the result, as in `EfVec::mmap(path)?.validate()`, to also verify
+    /// the chunks without copying them.
+    ///
+    /// # Safety
+    /// The file must have been written by [`EfVec::save`], and must not be
+    /// modified while it is mapped.
+    pub unsafe fn mmap(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let mmap = memmap2::Mmap::map(&file)?;
+        let Some(b) = mmap.first_chunk::<HEADER_SIZE>() else {
+            return Err(invalid("File is too short".to_string()));
+        };
+        let header = Header::parse::<C>(b)?;
+        if Some(mmap.len() as u64) != header.file_size::<C>() {
+            return Err(invalid("File size does not match header".to_string()));
+        }
+        // Maps are page-aligned, so this only fails for chunks that are
+        // aligned to more than a page.
+        if !(mmap.as_ptr() as usize + data_offset::<C>()).is_multiple_of(align_of::<C>()) {
+            return Err(invalid(format!(
+                "Memory map is not aligned to {} bytes",
+                align_of::<C>()
+            )));
+        }
+        Ok(Self {
+            ef: Mmapped {
+                mmap,
+                num_chunks: header.num_chunks,
+                _chunk: PhantomData,
+            },
+            len: header.len,
+            _chunk: PhantomData,
+        })
+    }
+}
+
+#[test]
+fn save_load() {
+    let vals = crate::test_vals(10000, 1000, 100);
+    let ef = crate::CachelineEfVec::new(&vals);
+    let path = std::env::temp_dir().join(format!("cacheline-ef-{}.bin", std::process::id()));
+    ef.save(&path).unwrap();
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().len() as usize,
+        HEADER_SIZE + ef.size_in_bytes()
+    );
+
+    let ef2 = crate::CachelineEfVec::load(&path).unwrap();
+    assert!(ef2.iter().eq(vals.iter().copied()));
+    #[cfg(feature = "mmap")]
+    {
+        let ef3 = unsafe { EfVec::<crate::CachelineEf, _>::mmap(&path).unwrap() };
+        assert!(ef3.iter().eq(vals.iter().copied()));
+    }
+
+    // Other chunk types are rejected.
+    let err = crate::PackedEfVec::<6>::load(&path).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    // Also when they have the same size and number of values per chunk.
+    assert_eq!(
+        size_of::<crate::CachelineEfWide>(),
+        size_of::<crate::PackedEf<9>>()
+    );
+    assert_eq!(crate::CachelineEfWide::L, crate::PackedEf::<9>::L);
+    crate::CachelineEfWideVec::new(&vals).save(&path).unwrap();
+    let err = crate::PackedEfVec::<9>::load(&path).err().unwrap();
+    assert!(err.to_string().contains("chunks of type"), "{err}");
+    #[cfg(feature = "mmap")]
+    {
+        let err = unsafe { EfVec::<crate::PackedEf<9>, _>::mmap(&path) }
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+    ef.save(&path).unwrap();
+
+    // Corrupted chunks are rejected.
+    let mut data = std::fs::read(&path).unwrap();
+    data[HEADER_SIZE + 100] ^= 1;
+    std::fs::write(&path, &data).unwrap();
+    let err = crate::CachelineEfVec::load(&path).err().unwrap();
+    assert_eq!(err.to_string(), "Checksum mismatch");
+
+    // Truncated files are rejected.
+    data.truncate(data.len() - 64);
+    std::fs::write(&path, &data).unwrap();
+    assert!(crate::CachelineEfVec::load(&path).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,114 @@
+//! Implementations of the [`sux`] traits for indexed sequences and dictionaries,
+//! so that an [`EfVec`] can replace `sux`'s `EliasFano`.
+
+use crate::{EfChunk, EfVec};
+use std::borrow::Borrow;
+use sux::traits::{IndexedDict, IndexedSeq, Pred, PredUnchecked, Succ, SuccUnchecked, Types};
+
+impl<C: EfChunk, E: AsRef<[C]>> Types for EfVec<C, E> {
+    type Input = u64;
+    type Output<'a> = u64;
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> IndexedSeq for EfVec<C, E> {
+    unsafe fn get_unchecked(&self, index: usize) -> u64 {
+        self.index_unchecked(index)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> IndexedDict for EfVec<C, E> {
+    /// Returns the position of the first copy of `value`.
+    fn index_of(&self, value: impl Borrow<u64>) -> Option<usize> {
+        let value = *value.borrow();
+        match self.next_geq(value) {
+            Some((index, v)) if v == value => Some(index),
+            _ => None,
+        }
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> SuccUnchecked for EfVec<C, E> {
+    unsafe fn succ_unchecked<const STRICT: bool>(&self, value: impl Borrow<u64>) -> (usize, u64) {
+        let value = *value.borrow();
+        // A strict successor exists, so `value + 1` does not overflow.
+        let value = if STRICT { value + 1 } else { value };
+        self.next_geq(value).unwrap_unchecked()
+    }
+}
+
+/// Successors are the first copy of repeated values.
+impl<C: EfChunk, E: AsRef<[C]>> Succ for EfVec<C, E> {
+    fn succ(&self, value: impl Borrow<u64>) -> Option<(usize, u64)> {
+        self.next_geq(*value.borrow())
+    }
+
+    fn succ_strict(&self, value: impl Borrow<u64>) -> Option<(usize, u64)> {
+        self.next_geq(value.borrow().checked_add(1)?)
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> PredUnchecked for EfVec<C, E> {
+    unsafe fn pred_unchecked<const STRICT: bool>(&self, value: impl Borrow<u64>) -> (usize, u64) {
+        let value = *value.borrow();
+        // A strict predecessor exists, so `value - 1` does not underflow.
+        let value = if STRICT { value - 1 } else { value };
+        self.prev_leq(value).unwrap_unchecked()
+    }
+
+    unsafe fn rank_unchecked(&self, value: impl Borrow<u64>) -> usize {
+        EfVec::rank(self, *value.borrow())
+    }
+}
+
+/// Predecessors are the last copy of repeated values.
+impl<C: EfChunk, E: AsRef<[C]>> Pred for EfVec<C, E> {
+    fn pred(&self, value: impl Borrow<u64>) -> Option<(usize, u64)> {
+        self.prev_leq(*value.borrow())
+    }
+
+    fn pred_strict(&self, value: impl Borrow<u64>) -> Option<(usize, u64)> {
+        self.prev_leq(value.borrow().checked_sub(1)?)
+    }
+
+    fn rank(&self, value: impl Borrow<u64>) -> usize {
+        EfVec::rank(self, *value.borrow())
+    }
+}
+
+#[test]
+fn sux_traits() {
+    // Only uses the `sux` traits, as code that is generic over `EliasFano`.
+    fn check<T>(d: &T, vals: &[u64])
+    where
+        T: for<'a> Types<Input = u64, Output<'a> = u64> + IndexedSeq + IndexedDict + Succ + Pred,
+    {
+        assert_eq!(IndexedSeq::len(d), vals.len());
+        for (i, &v) in vals.iter().enumerate() {
+            assert_eq!(IndexedSeq::get(d, i), v);
+        }
+        assert_eq!(d.first_value(), vals.first().copied());
+        assert_eq!(d.last_value(), vals.last().copied());
+        for _ in 0..10000 {
+            let x = rand::random::<u64>() % (vals[vals.len() - 1] + 10);
+            let lt = vals.partition_point(|&v| v < x);
+            let leq = vals.partition_point(|&v| v <= x);
+            assert_eq!(d.succ(x), vals.get(lt).map(|&v| (lt, v)));
+            assert_eq!(d.succ_strict(x), vals.get(leq).map(|&v| (leq, v)));
+            assert_eq!(d.pred(x), leq.checked_sub(1).map(|i| (i, vals[i])));
+            assert_eq!(d.pred_strict(x), lt.checked_sub(1).map(|i| (i, vals[i])));
+            assert_eq!(Pred::rank(d, x), lt);
+            assert_eq!(d.index_of(x), (lt < leq).then_some(lt));
+            assert_eq!(d.contains(x), lt < leq);
+        }
+        assert_eq!(d.succ_strict(u64::MAX), None);
+        assert_eq!(d.pred_strict(0), None);
+    }
+
+    let vals = crate::test_vals(10000, 1000, 10);
+    check(&crate::CachelineEfVec::new(&vals), &vals);
+    check(&crate::PackedEfVec::<3>::new(&vals), &vals);
+}
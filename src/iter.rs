@@ -0,0 +1,139 @@
+use crate::{EfChunk, EfVec};
+use std::iter::FusedIterator;
+
+/// Iterator over the values of an [`EfVec`].
+///
+/// Chunks are decoded as a whole when the iterator enters them, so that each
+/// value costs a constant amount of work instead of a `select` per value.
+pub struct Iter<'a, C, E> {
+    ef: &'a EfVec<C, E>,
+    /// Index of the next value returned by `next`.
+    front: usize,
+    /// One past the index of the next value returned by `next_back`.
+    back: usize,
+    /// The decoded values of chunk `front_chunk`.
+    front_chunk: usize,
+    front_vals: Vec<u64>,
+    /// The decoded values of chunk `back_chunk`.
+    back_chunk: usize,
+    back_vals: Vec<u64>,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> EfVec<C, E> {
+    /// Iterate over all values.
+    pub fn iter(&self) -> Iter<'_, C, E> {
+        self.iter_from(0)
+    }
+
+    /// Iterate over the values starting at position `index`.
+    pub fn iter_from(&self, index: usize) -> Iter<'_, C, E> {
+        assert!(
+            index <= self.len,
+            "Index {index} out of bounds. Length is {}.",
+            self.len
+        );
+        Iter {
+            ef: self,
+            front: index,
+            back: self.len,
+            front_chunk: usize::MAX,
+            front_vals: Vec::with_capacity(C::L),
+            back_chunk: usize::MAX,
+            back_vals: Vec::with_capacity(C::L),
+        }
+    }
+}
+
+impl<'a, C: EfChunk, E: AsRef<[C]>> IntoIterator for &'a EfVec<C, E> {
+    type Item = u64;
+    type IntoIter = Iter<'a, C, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> Iterator for Iter<'_, C, E> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.front == self.back {
+            return None;
+        }
+        let k = self.front / C::L;
+        if k != self.front_chunk {
+            self.ef.chunk_decode(k, &mut self.front_vals);
+            self.front_chunk = k;
+        }
+        let v = self.front_vals[self.front % C::L];
+        self.front += 1;
+        Some(v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+
+    /// Skip `n` values without decoding the chunks in between.
+    fn nth(&mut self, n: usize) -> Option<u64> {
+        self.front += n.min(self.back - self.front);
+        self.next()
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> DoubleEndedIterator for Iter<'_, C, E> {
+    fn next_back(&mut self) -> Option<u64> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        let k = self.back / C::L;
+        if k != self.back_chunk {
+            self.ef.chunk_decode(k, &mut self.back_vals);
+            self.back_chunk = k;
+        }
+        Some(self.back_vals[self.back % C::L])
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> ExactSizeIterator for Iter<'_, C, E> {}
+
+impl<C: EfChunk, E: AsRef<[C]>> FusedIterator for Iter<'_, C, E> {}
+
+#[test]
+fn iter() {
+    let vals = crate::test_vals(10000, 1000, 100);
+    let ef = crate::CachelineEfVec::new(&vals);
+    assert!(ef.iter().eq(vals.iter().copied()));
+    assert!((&ef).into_iter().rev().eq(vals.iter().copied().rev()));
+    assert_eq!(ef.iter().len(), vals.len());
+    for start in [0, 1, 43, 44, 45, 5000, 9999, 10000] {
+        assert!(ef.iter_from(start).eq(vals[start..].iter().copied()));
+    }
+
+    // Alternate between both ends, so that they meet within one chunk.
+    let mut it = ef.iter_from(100);
+    let (mut front, mut back) = (100, vals.len());
+    while front < back {
+        assert_eq!(it.len(), back - front);
+        if rand::random::<bool>() {
+            assert_eq!(it.next(), Some(vals[front]));
+            front += 1;
+        } else {
+            back -= 1;
+            assert_eq!(it.next_back(), Some(vals[back]));
+        }
+    }
+    assert_eq!(it.next(), None);
+    assert_eq!(it.next_back(), None);
+
+    let mut it = ef.iter();
+    assert_eq!(it.nth(50), Some(vals[50]));
+    assert_eq!(it.next(), Some(vals[51]));
+    assert_eq!(it.nth(5000), Some(vals[5052]));
+    assert_eq!(it.nth(10000), None);
+
+    let ef = crate::PackedEfVec::<4>::new(&vals[..1000]);
+    assert!(ef.iter().eq(vals[..1000].iter().copied()));
+}
@@ -1,5 +1,45 @@
 use common_traits::SelectInWord;
-use std::cmp::min;
+use std::{cmp::min, marker::PhantomData};
+
+mod batch;
+mod bitvec;
+mod builder;
+mod concat;
+mod error;
+mod estimate;
+mod file;
+#[cfg(feature = "sux")]
+mod indexed_dict;
+mod iter;
+mod offsets;
+mod packed;
+#[cfg(feature = "serde")]
+mod serialize;
+mod set_ops;
+mod stats;
+mod strict;
+mod strings;
+mod wide;
+
+pub use batch::{BatchIter, DEFAULT_PREFETCH_DISTANCE};
+pub use bitvec::{CachelineEfBitVec, EfBitVec};
+pub use builder::{CachelineEfVecBuilder, EfVecBuilder};
+pub use error::CachelineEfError;
+pub use estimate::SpaceEstimate;
+#[cfg(feature = "mmap")]
+pub use file::Mmapped;
+pub use iter::Iter;
+pub use offsets::{
+    CachelineEfOffsets, CachelineEfOffsetsBuilder, EfOffsets, EfOffsetsBuilder, OffsetsIter,
+};
+pub use packed::{PackedEf, PackedEfVec};
+pub use set_ops::{Difference, Intersect, IntersectAll, Union, UnionAll};
+pub use stats::EfStats;
+pub use strict::{CachelineStrictEfVec, StrictEfVec, StrictIter};
+pub use strings::{
+    CachelineEfStrings, CachelineEfStringsBuilder, EfStrings, EfStringsBuilder, StringsIter,
+};
+pub use wide::{CachelineEfWide, CachelineEfWideVec};
 
 /// Number of stored values per unit.
 const L: usize = 44;
@@ -10,154 +50,851 @@ const L: usize = 44;
 ///
 /// This is efficient when consecutive values differ by roughly 100, where using
 /// Elias-Fano directly on the full list would use around 9 bits/value.
+/// For other densities, see [`PackedEfVec`], for values of 2^40 and
+/// more, see [`CachelineEfWideVec`], and for 32 or 128 byte lines, see
+/// [`CachelineEf32Vec`] and [`CachelineEf128Vec`].
 ///
 /// The main benefit is that this only requires reading a single cacheline per
 /// query, where Elias-Fano encoding usually needs 3 reads.
-#[derive(Default, Clone, mem_dbg::MemSize, mem_dbg::MemDbg)]
+pub type CachelineEfVec<E = Vec<CachelineEf>> = EfVec<CachelineEf, E>;
+
+/// A non-decreasing list of values, encoded in chunks of type `C` that each
+/// occupy a single cacheline.
+///
+/// Chunks whose values can not be encoded by `C` (because they span a too
+/// large range or are too large) are _escaped_: their values are stored as
+/// plain `u64`s in overflow cachelines at the end of `ef`, and querying them
+/// reads one additional cacheline.
+#[derive(Clone, mem_dbg::MemSize, mem_dbg::MemDbg)]
 #[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
-pub struct CachelineEfVec<E = Vec<CachelineEf>> {
+pub struct EfVec<C, E = Vec<C>> {
+    // `len.div_ceil(C::L)` chunks, followed by the overflow cachelines of escaped chunks.
     ef: E,
     len: usize,
+    _chunk: PhantomData<C>,
 }
 
-impl CachelineEfVec<Vec<CachelineEf>> {
+impl<C, E: Default> Default for EfVec<C, E> {
+    fn default() -> Self {
+        Self {
+            ef: E::default(),
+            len: 0,
+            _chunk: PhantomData,
+        }
+    }
+}
+
+impl<C: EfChunk> EfVec<C, Vec<C>> {
+    /// Encode a non-decreasing list of values.
+    ///
+    /// Panics when the values are not sorted; see [`Self::try_new`].
     pub fn new(vals: &[u64]) -> Self {
-        let mut p = Vec::with_capacity(vals.len().div_ceil(L));
-        for i in (0..vals.len()).step_by(L) {
-            p.push(CachelineEf::new(&vals[i..min(i + L, vals.len())]));
+        Self::try_new(vals).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Encode a non-decreasing list of values, or return an error explaining
+    /// where the values are not sorted.
+    ///
+    /// Chunks of [`EfChunk::L`] consecutive values that can not be encoded
+    /// are escaped. For [`CachelineEf`], this happens when they span a range
+    /// of more than 256*84=21504, or contain values of at least 2^40.
+    pub fn try_new(vals: &[u64]) -> Result<Self, CachelineEfError> {
+        let mut builder = EfVecBuilder::with_capacity(vals.len());
+        for &v in vals {
+            builder.try_push(v)?;
         }
+        Ok(builder.finish())
+    }
+}
 
-        Self {
-            ef: p,
-            len: vals.len(),
+impl<'a, C: EfChunk> EfVec<C, &'a [C]> {
+    /// View bytes returned by [`EfVec::as_bytes`] as a vector of `len`
+    /// values, without copying them.
+    ///
+    /// Returns an error when `bytes` is not aligned to the alignment of `C`,
+    /// its size is not a multiple of the chunk size, or the chunks are not
+    /// valid encodings of `len` values.
+    pub fn from_bytes(bytes: &'a [u8], len: usize) -> Result<Self, CachelineEfError> {
+        if !(bytes.as_ptr() as usize).is_multiple_of(align_of::<C>()) {
+            return Err(CachelineEfError::Misaligned {
+                align: align_of::<C>(),
+            });
         }
+        if !bytes.len().is_multiple_of(size_of::<C>()) {
+            return Err(CachelineEfError::InvalidSize {
+                size: bytes.len(),
+                chunk_size: size_of::<C>(),
+            });
+        }
+        // SAFETY: Alignment and size were checked, and `EfChunk` guarantees
+        // that any bit pattern is valid.
+        let ef = unsafe {
+            std::slice::from_raw_parts(bytes.as_ptr() as *const C, bytes.len() / size_of::<C>())
+        };
+        let ef = Self {
+            ef,
+            len,
+            _chunk: PhantomData,
+        };
+        ef.validate()?;
+        Ok(ef)
     }
 }
 
-impl<E: AsRef<[CachelineEf]>> CachelineEfVec<E> {
+impl<C: EfChunk, E: AsRef<[C]>> EfVec<C, E> {
     pub fn index(&self, index: usize) -> u64 {
         assert!(
             index < self.len,
             "Index {index} out of bounds. Length is {}.",
             self.len
         );
-        // Note: This division is inlined by the compiler.
-        unsafe { self.ef.as_ref().get_unchecked(index / L).get(index % L) }
+        unsafe { self.index_unchecked(index) }
     }
     pub fn len(&self) -> usize {
         self.len
     }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// # Safety
+    /// `index` must be less than `self.len()`. For data that was not
+    /// constructed by this crate, see [`Self::validate`].
     pub unsafe fn index_unchecked(&self, index: usize) -> u64 {
         // Note: This division is inlined by the compiler.
-        (*self.ef.as_ref().get_unchecked(index / L)).get(index % L)
+        let c = self.ef.as_ref().get_unchecked(index / C::L);
+        match escaped(c) {
+            None => c.get(index % C::L),
+            Some(block) => self.overflow(index / C::L, block)[index % C::L],
+        }
     }
+    /// Prefetch the cacheline containing `index` into L1 cache.
     pub fn prefetch(&self, index: usize) {
-        prefetch_index(self.ef.as_ref(), index / L);
+        self.prefetch_with(index, Locality::L1);
+    }
+    /// Prefetch the cacheline containing `index` into the given cache level.
+    pub fn prefetch_with(&self, index: usize, locality: Locality) {
+        prefetch_index(self.ef.as_ref(), index / C::L, locality);
     }
     pub fn size_in_bytes(&self) -> usize {
         std::mem::size_of_val(self.ef.as_ref())
     }
+
+    /// The raw bytes of the chunks, which can be turned back into a vector of
+    /// [`Self::len`] values by [`EfVec::from_bytes`].
+    pub fn as_bytes(&self) -> &[u8] {
+        let ef = self.ef.as_ref();
+        // SAFETY: `EfChunk` guarantees that chunks do not contain padding.
+        unsafe { std::slice::from_raw_parts(ef.as_ptr() as *const u8, size_of_val(ef)) }
+    }
+
+    /// Return the position and value of the first stored value `>= x`, or
+    /// `None` when all values are smaller than `x`.
+    ///
+    /// The chunk is found by a binary search over the first value of each
+    /// chunk, after which only a single cacheline is scanned.
+    pub fn next_geq(&self, x: u64) -> Option<(usize, u64)> {
+        let chunks = self.chunks();
+        // The answer is either in the last chunk starting below `x`, or it is
+        // the first value of the next chunk.
+        let k = chunks.partition_point(|c| first(c) < x);
+        if k > 0 {
+            let r = self.chunk_rank(k - 1, x);
+            if r < self.chunk_len(k - 1) {
+                return Some(((k - 1) * C::L + r, self.chunk_get(k - 1, r)));
+            }
+        }
+        chunks.get(k).map(|c| (k * C::L, first(c)))
+    }
+
+    /// Return the position and value of the last stored value `<= x`, or
+    /// `None` when all values are larger than `x`.
+    ///
+    /// When `x` occurs multiple times, the position of the last copy is returned.
+    pub fn prev_leq(&self, x: u64) -> Option<(usize, u64)> {
+        // The answer is in the last chunk starting at or below `x`.
+        let k = self.chunks().partition_point(|c| first(c) <= x);
+        if k == 0 {
+            return None;
+        }
+        let r = x
+            .checked_add(1)
+            .map_or(self.chunk_len(k - 1), |x| self.chunk_rank(k - 1, x));
+        Some(((k - 1) * C::L + r - 1, self.chunk_get(k - 1, r - 1)))
+    }
+
+    /// The number of stored values strictly less than `x`.
+    ///
+    /// This is also the position of the first copy of `x`, if present.
+    pub fn rank(&self, x: u64) -> usize {
+        let k = self.chunks().partition_point(|c| first(c) < x);
+        if k == 0 {
+            return 0;
+        }
+        (k - 1) * C::L + self.chunk_rank(k - 1, x)
+    }
+
+    /// Check that the data is a valid encoding of a non-decreasing list of
+    /// [`Self::len`] values, and return the first problem found otherwise.
+    ///
+    /// This checks that there are enough chunks for `len` values, that each
+    /// chunk encodes the right number of values (e.g. the popcount of
+    /// `high_boundaries` for [`CachelineEf`]) below the bound of its encoding,
+    /// that escaped chunks point to existing overflow cachelines, and that
+    /// values are non-decreasing within and across chunks.
+    ///
+    /// [`EfVec::load`], [`EfVec::from_bytes`] and `serde` deserialization call
+    /// this automatically. With the `epserde` feature, data is not checked on
+    /// deserialization, so data that is not trusted should be validated after
+    /// loading, as in `CachelineEfVec::load_mem(path)?.validate()?`. After
+    /// successful validation, queries do not panic, and
+    /// [`Self::index_unchecked`] is sound for indices below [`Self::len`].
+    pub fn validate(&self) -> Result<(), CachelineEfError> {
+        let ef = self.ef.as_ref();
+        let num_chunks = self.len.div_ceil(C::L);
+        if ef.len() < num_chunks {
+            return Err(CachelineEfError::TooFewChunks {
+                len: self.len,
+                expected: num_chunks,
+                found: ef.len(),
+            });
+        }
+        let num_blocks = ef.len() - num_chunks;
+        let mut vals = Vec::with_capacity(C::L);
+        let mut prev = 0;
+        for (k, c) in ef[..num_chunks].iter().enumerate() {
+            let n = self.chunk_len(k);
+            match escaped(c) {
+                Some(block) => {
+                    if block.saturating_add(n.div_ceil(words::<C>())) > num_blocks {
+                        return Err(CachelineEfError::EscapeOutOfBounds {
+                            chunk: k,
+                            block,
+                            num_blocks,
+                        });
+                    }
+                }
+                None => c.validate(n, k)?,
+            }
+            self.chunk_decode(k, &mut vals);
+            if first(c) != vals[0] {
+                return Err(CachelineEfError::WrongFirstValue {
+                    chunk: k,
+                    expected: vals[0],
+                    found: first(c),
+                });
+            }
+            for (i, &v) in vals.iter().enumerate() {
+                if v < prev {
+                    return Err(CachelineEfError::NotSorted {
+                        chunk: k,
+                        index: k * C::L + i,
+                        prev,
+                        value: v,
+                    });
+                }
+                prev = v;
+            }
+        }
+        Ok(())
+    }
+
+    /// The chunks, without the trailing overflow cachelines.
+    fn chunks(&self) -> &[C] {
+        &self.ef.as_ref()[..self.len.div_ceil(C::L)]
+    }
+
+    /// The values of escaped chunk `k`, whose overflow starts at `block`.
+    fn overflow(&self, k: usize, block: usize) -> &[u64] {
+        let n = self.chunk_len(k);
+        let start = self.len.div_ceil(C::L) + block;
+        let blocks = &self.ef.as_ref()[start..start + n.div_ceil(words::<C>())];
+        // SAFETY: `EfChunk` guarantees that chunks consist of `words::<C>()` plain words.
+        unsafe { std::slice::from_raw_parts(blocks.as_ptr() as *const u64, n) }
+    }
+
+    /// The number of values in chunk `k`. Only the last chunk can be partial.
+    fn chunk_len(&self, k: usize) -> usize {
+        min(C::L, self.len - k * C::L)
+    }
+
+    fn chunk_get(&self, k: usize, idx: usize) -> u64 {
+        let c = &self.chunks()[k];
+        match escaped(c) {
+            None => c.get(idx),
+            Some(block) => self.overflow(k, block)[idx],
+        }
+    }
+
+    /// Decode all values of chunk `k` into `out`.
+    fn chunk_decode(&self, k: usize, out: &mut Vec<u64>) {
+        out.resize(self.chunk_len(k), 0);
+        let c = &self.chunks()[k];
+        match escaped(c) {
+            None => c.decode(out),
+            Some(block) => out.copy_from_slice(self.overflow(k, block)),
+        }
+    }
+
+    fn chunk_rank(&self, k: usize, x: u64) -> usize {
+        let c = &self.chunks()[k];
+        match escaped(c) {
+            None => c.rank(x),
+            Some(block) => self.overflow(k, block).partition_point(|&v| v < x),
+        }
+    }
+}
+
+/// An encoding of up to [`EfChunk::L`] non-decreasing values in a single cacheline.
+///
+/// # Safety
+///
+/// [`EfVec`] stores escaped chunks and their overflow values in the same array
+/// as regular chunks, by reinterpreting chunks as `u64` words. Implementors
+/// must be `repr(C)` without padding, aligned to at least 8 bytes, consist of
+/// at least 3 words, and be valid for any bit pattern. The first word of every
+/// chunk returned by [`EfChunk::try_new`] must be non-zero.
+// Chunks are never empty.
+#[allow(clippy::len_without_is_empty)]
+pub unsafe trait EfChunk: Copy {
+    /// The maximum number of values in a chunk.
+    const L: usize;
+    /// The maximum range of the values in a chunk, as reported by
+    /// [`CachelineEfError::RangeTooLarge`].
+    const MAX_RANGE: u64;
+    /// Identifies the layout of the chunk in files written by [`EfVec::save`].
+    ///
+    /// Chunk types with the same size and `L` must have different tags. The
+    /// chunk types of this crate store a type id in the top byte, and their
+    /// parameters in the lower bytes.
+    const TAG: u64;
+
+    /// Encode between 1 and `L` sorted values, where `chunk` is only used for error reporting.
+    fn try_new(vals: &[u64], chunk: usize) -> Result<Self, CachelineEfError>;
+    /// The `idx`'th value.
+    fn get(&self, idx: usize) -> u64;
+    /// The number of stored values.
+    fn len(&self) -> usize;
+    /// The first stored value.
+    fn first(&self) -> u64 {
+        self.get(0)
+    }
+    /// The number of stored values less than `x`.
+    fn rank(&self, x: u64) -> usize;
+    /// The number of bits of the unary high part after its last 1-bit, i.e.
+    /// the number of 0-bits the largest value could still use.
+    fn unused_high_bits(&self) -> usize;
+    /// Check that the chunk is a valid encoding of `len` values, where
+    /// `chunk` is only used for error reporting.
+    ///
+    /// This guarantees that querying the chunk does not panic or overflow.
+    fn validate(&self, len: usize, chunk: usize) -> Result<(), CachelineEfError>;
+    /// Decode the first `out.len()` values.
+    fn decode(&self, out: &mut [u64]) {
+        for (i, v) in out.iter_mut().enumerate() {
+            *v = self.get(i);
+        }
+    }
+}
+
+// Escaped chunks have a first word of 0, followed by the index of their first
+// overflow cacheline (relative to the end of the chunks) and their first value.
+
+/// The number of `u64` words in a chunk.
+fn words<C>() -> usize {
+    std::mem::size_of::<C>() / 8
+}
+
+/// A chunk consisting of the given words, padded with zeros.
+fn from_words<C: EfChunk>(words: &[u64]) -> C {
+    // SAFETY: `EfChunk` guarantees that any bit pattern is valid.
+    let mut c: C = unsafe { std::mem::zeroed() };
+    let c_words =
+        unsafe { std::slice::from_raw_parts_mut(&mut c as *mut C as *mut u64, self::words::<C>()) };
+    c_words[..words.len()].copy_from_slice(words);
+    c
+}
+
+/// For escaped chunks, the index of the first overflow cacheline.
+fn escaped<C: EfChunk>(c: &C) -> Option<usize> {
+    let w = c as *const C as *const u64;
+    // SAFETY: `EfChunk` guarantees that chunks consist of at least 3 words.
+    unsafe { (*w == 0).then(|| *w.add(1) as usize) }
+}
+
+/// Check that `vals` is a non-empty, sorted list of at most `C::L` values,
+/// which is what `EfChunk::try_new` accepts.
+fn check_chunk<C: EfChunk>(vals: &[u64], chunk: usize) -> Result<(), CachelineEfError> {
+    if vals.is_empty() {
+        return Err(CachelineEfError::EmptyChunk { chunk });
+    }
+    if vals.len() > C::L {
+        return Err(CachelineEfError::TooManyValues {
+            chunk,
+            len: vals.len(),
+            max: C::L,
+        });
+    }
+    for i in 1..vals.len() {
+        if vals[i - 1] > vals[i] {
+            return Err(CachelineEfError::NotSorted {
+                chunk,
+                index: chunk * C::L + i,
+                prev: vals[i - 1],
+                value: vals[i],
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The first value of a possibly escaped chunk.
+fn first<C: EfChunk>(c: &C) -> u64 {
+    match escaped(c) {
+        // SAFETY: See `escaped`.
+        Some(_) => unsafe { *(c as *const C as *const u64).add(2) },
+        None => c.first(),
+    }
 }
 
 /// Single-cacheline Elias-Fano encoding that holds 44 40-bit values in a range of size 256*84=21504.
-// This has size 64 bytes (one cacheline) and is aligned to 64bytes as well to
-// ensure it actually occupied a single cacheline.
+pub type CachelineEf = EfLine<2, L, Align64>;
+
+/// Half-cacheline Elias-Fano encoding in 32 bytes that holds 20 40-bit values
+/// in a range of size 256*44=11264, using 12.8 bits per value.
+pub type CachelineEf32 = EfLine<1, 20, Align32>;
+
+/// Elias-Fano encoding for 128-byte cachelines (e.g. Apple M-series) that holds
+/// 92 40-bit values in a range of size 256*164=41984, using 11.1 bits per value.
+pub type CachelineEf128 = EfLine<4, 92, Align128>;
+
+/// A list of values encoded in [`CachelineEf32`] chunks.
+pub type CachelineEf32Vec<E = Vec<CachelineEf32>> = EfVec<CachelineEf32, E>;
+
+/// A list of values encoded in [`CachelineEf128`] chunks.
+pub type CachelineEf128Vec<E = Vec<CachelineEf128>> = EfVec<CachelineEf128, E>;
+
+/// Zero-sized types that set the alignment of an [`EfLine`].
+///
+/// This trait is sealed; it is only implemented by [`Align32`], [`Align64`]
+/// and [`Align128`].
+pub trait Alignment: sealed::Sealed + Copy + 'static {}
+
+impl Alignment for Align32 {}
+impl Alignment for Align64 {}
+impl Alignment for Align128 {}
+
+mod sealed {
+    use super::*;
+
+    pub trait Sealed {}
+
+    impl Sealed for Align32 {}
+    impl Sealed for Align64 {}
+    impl Sealed for Align128 {}
+
+    /// The shapes of [`EfLine`] whose size equals their alignment, i.e. that
+    /// have no padding. Only these implement [`EfChunk`].
+    pub trait Line {}
+
+    impl Line for CachelineEf {}
+    impl Line for CachelineEf32 {}
+    impl Line for CachelineEf128 {}
+}
+
+/// Aligns an [`EfLine`] to 32 bytes.
+#[derive(Clone, Copy, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[repr(align(32))]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+#[cfg_attr(feature = "epserde", zero_copy)]
+#[copy_type]
+pub struct Align32;
+
+/// Aligns an [`EfLine`] to 64 bytes.
+#[derive(Clone, Copy, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[repr(align(64))]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+#[cfg_attr(feature = "epserde", zero_copy)]
+#[copy_type]
+pub struct Align64;
+
+/// Aligns an [`EfLine`] to 128 bytes.
+#[derive(Clone, Copy, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[repr(align(128))]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+#[cfg_attr(feature = "epserde", zero_copy)]
+#[copy_type]
+pub struct Align128;
+
+/// Elias-Fano encoding of `N` 40-bit values in a single line of `8*W + 4 + N`
+/// bytes, aligned to the line size by `A`.
+///
+/// The high parts are stored in unary in `64*W` bits, so that a chunk can span
+/// a range of `256*(64*W - N)`. Only [`CachelineEf`], [`CachelineEf32`] and
+/// [`CachelineEf128`] implement [`EfChunk`].
+// The size of the line must equal its alignment, so that each line occupies
+// exactly one (half-)cacheline. This is checked by `Self::CHECK`.
 // It is marked `zero_copy` to be able to use it with lazy deserialization of ep-serde.
 #[derive(Clone, Copy, mem_dbg::MemSize, mem_dbg::MemDbg)]
 #[repr(C)]
-#[repr(align(64))]
 #[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
 #[cfg_attr(feature = "epserde", zero_copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "serialize::ChunkRepr<u32>",
+        into = "serialize::ChunkRepr<u32>",
+        bound = "A: Alignment"
+    )
+)]
 #[copy_type]
-pub struct CachelineEf {
-    // 2*64 = 128 bits to indicate where 256 boundaries are crossed.
-    // There are 44 1-bits corresponding to the stored numbers, and the number
+pub struct EfLine<const W: usize, const N: usize, A: Alignment> {
+    // 64*W bits to indicate where 256 boundaries are crossed.
+    // There are N 1-bits corresponding to the stored numbers, and the number
     // of 0-bits before each number indicates the number of times 256 must be added.
-    high_boundaries: [u64; 2],
+    high_boundaries: [u64; W],
     // The offset of the first element, divided by 256.
     reduced_offset: u32,
     // Last 8 bits of each number.
-    low_bits: [u8; L],
+    low_bits: [u8; N],
+    _align: [A; 0],
 }
 
-impl CachelineEf {
-    fn new(vals: &[u64]) -> Self {
-        assert!(!vals.is_empty(), "List of values must not be empty.");
-        assert!(
-            vals.len() <= L,
-            "Number of values must be at most {L}, but is {}",
-            vals.len()
-        );
+impl<const W: usize, const N: usize, A: Alignment> EfLine<W, N, A> {
+    const CHECK: () = assert!(
+        size_of::<Self>() == 8 * W + 4 + N && align_of::<Self>() == size_of::<Self>(),
+        "The size of the line must equal its alignment."
+    );
+
+    fn view(&self) -> EfView<'_, W> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CHECK;
+        EfView {
+            high_boundaries: &self.high_boundaries,
+            reduced_offset: self.reduced_offset as u64,
+            low_bits: &self.low_bits,
+        }
+    }
+}
+
+// SAFETY: Only the shapes in `sealed::Line` implement `EfChunk`, and `CHECK`
+// ensures that these have no padding. Each line consists of at least 4 words.
+// Bit 0 of `high_boundaries` is set for the first value.
+unsafe impl<const W: usize, const N: usize, A: Alignment> EfChunk for EfLine<W, N, A>
+where
+    Self: sealed::Line,
+{
+    const L: usize = N;
+    const MAX_RANGE: u64 = 256 * (64 * W - N) as u64;
+    const TAG: u64 = 1 << 56 | (W as u64) << 32 | N as u64;
+
+    fn try_new(vals: &[u64], chunk: usize) -> Result<Self, CachelineEfError> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CHECK;
+        check_chunk::<Self>(vals, chunk)?;
         let l = vals.len();
-        assert!(
-            vals[l - 1] - vals[0] <= 256 * (128 - L as u64),
-            "Range of values {} ({} to {}) is too large! Can be at most {}.",
-            vals[l - 1] - vals[0],
-            vals[0],
-            vals[l - 1],
-            256 * (128 - L as u64)
-        );
-        assert!(
-            vals[l - 1] < (1 << 40),
-            "Last value {} is too large! Must be less than 2^40={}",
-            vals[l - 1],
-            1 << 40
-        );
+        if vals[l - 1] - vals[0] > Self::MAX_RANGE {
+            return Err(CachelineEfError::RangeTooLarge {
+                chunk,
+                first: vals[0],
+                last: vals[l - 1],
+                max_range: Self::MAX_RANGE,
+            });
+        }
+        if vals[l - 1] >= 1 << 40 {
+            return Err(CachelineEfError::ValueTooLarge {
+                chunk,
+                value: vals[l - 1],
+                bound: 1 << 40,
+            });
+        }
+
+        let mut high_boundaries = [0u64; W];
+        let mut low_bits = [0u8; N];
+        // Since values are sorted and less than 2^40, this fits in a u32.
+        let offset = EfView::encode(vals, &mut high_boundaries, &mut low_bits);
+        Ok(Self {
+            reduced_offset: offset as u32,
+            high_boundaries,
+            low_bits,
+            _align: [],
+        })
+    }
+
+    fn get(&self, idx: usize) -> u64 {
+        self.view().get(idx)
+    }
+    fn len(&self) -> usize {
+        self.view().len()
+    }
+    fn first(&self) -> u64 {
+        self.view().first()
+    }
+    fn rank(&self, x: u64) -> usize {
+        self.view().rank(x)
+    }
+    fn unused_high_bits(&self) -> usize {
+        self.view().unused_high_bits()
+    }
+    fn validate(&self, len: usize, chunk: usize) -> Result<(), CachelineEfError> {
+        self.view().validate(len, chunk)?;
+        let last = self.get(len - 1);
+        if last >= 1 << 40 {
+            return Err(CachelineEfError::ValueTooLarge {
+                chunk,
+                value: last,
+                bound: 1 << 40,
+            });
+        }
+        Ok(())
+    }
+    fn decode(&self, out: &mut [u64]) {
+        self.view().decode(out)
+    }
+}
 
+/// The layout shared by [`EfLine`] and [`CachelineEfWide`]: the last 8 bits
+/// of each value, and the remaining high part in unary in `64*W` bits.
+struct EfView<'a, const W: usize> {
+    high_boundaries: &'a [u64; W],
+    // The offset of the first element, divided by 256.
+    reduced_offset: u64,
+    low_bits: &'a [u8],
+}
+
+impl<const W: usize> EfView<'_, W> {
+    /// Encode the values and return the reduced offset.
+    /// The caller must check that the range of the values fits.
+    fn encode(vals: &[u64], high_boundaries: &mut [u64; W], low_bits: &mut [u8]) -> u64 {
         let offset = vals[0] >> 8;
-        assert!(
-            offset <= u32::MAX as u64,
-            "vals[0] does not fit in 40 bits."
-        );
-        let mut low_bits = [0u8; L];
         for (i, &v) in vals.iter().enumerate() {
             low_bits[i] = (v & 0xff) as u8;
         }
-        let mut high_boundaries = [0u64; 2];
         for (i, &v) in vals.iter().enumerate() {
             let idx = i + ((v >> 8) - offset) as usize;
-            assert!(idx < 128, "Value {} is too large!", v - offset);
+            debug_assert!(idx < 64 * W, "Value {} is too large!", v - offset);
             high_boundaries[idx / 64] |= 1 << (idx % 64);
         }
-        Self {
-            reduced_offset: offset as u32,
-            high_boundaries,
-            low_bits,
-        }
+        offset
     }
 
     fn get(&self, idx: usize) -> u64 {
-        let p = self.high_boundaries[0].count_ones() as usize;
-        let one_pos = if idx < p {
-            self.high_boundaries[0].select_in_word(idx)
+        let one_pos = self.select(idx, true);
+        256 * self.reduced_offset + 256 * (one_pos - idx) as u64 + self.low_bits[idx] as u64
+    }
+
+    fn len(&self) -> usize {
+        self.high_boundaries
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum()
+    }
+
+    fn validate(&self, len: usize, chunk: usize) -> Result<(), CachelineEfError> {
+        if len == 0 {
+            return Err(CachelineEfError::EmptyChunk { chunk });
+        }
+        if len > self.low_bits.len() {
+            return Err(CachelineEfError::TooManyValues {
+                chunk,
+                len,
+                max: self.low_bits.len(),
+            });
+        }
+        let found = self.len();
+        if found != len {
+            return Err(CachelineEfError::WrongLength {
+                chunk,
+                expected: len,
+                found,
+            });
+        }
+        if self.high_boundaries[0] & 1 == 0 {
+            return Err(CachelineEfError::MissingFirstBit { chunk });
+        }
+        // The last value is the largest one.
+        let high = self.select(len - 1, true) - (len - 1);
+        self.reduced_offset
+            .checked_add(high as u64)
+            .and_then(|h| h.checked_mul(256))
+            .and_then(|h| h.checked_add(self.low_bits[len - 1] as u64))
+            .ok_or(CachelineEfError::DecodeOverflow { chunk })?;
+        Ok(())
+    }
+
+    fn unused_high_bits(&self) -> usize {
+        let used = match self.high_boundaries.iter().rposition(|&w| w != 0) {
+            Some(w) => 64 * w + 64 - self.high_boundaries[w].leading_zeros() as usize,
+            None => 0,
+        };
+        64 * W - used
+    }
+
+    /// The first value always corresponds to bit 0.
+    fn first(&self) -> u64 {
+        256 * self.reduced_offset + self.low_bits[0] as u64
+    }
+
+    /// Position of the `k`'th 1-bit (or 0-bit) in `high_boundaries`.
+    fn select(&self, mut k: usize, ones: bool) -> usize {
+        for (w, &word) in self.high_boundaries.iter().enumerate() {
+            let word = if ones { word } else { !word };
+            let cnt = word.count_ones() as usize;
+            if k < cnt {
+                return 64 * w + word.select_in_word(k);
+            }
+            k -= cnt;
+        }
+        unreachable!("Not enough bits set in high_boundaries.")
+    }
+
+    fn rank(&self, x: u64) -> usize {
+        let n = self.len();
+        let Some(bucket) = (x >> 8).checked_sub(self.reduced_offset) else {
+            return 0;
+        };
+        // There are `64*W - n` 0-bits, each closing one bucket of 256 values.
+        if bucket > (64 * W - n) as u64 {
+            return n;
+        }
+        let bucket = bucket as usize;
+        // Values in smaller buckets are exactly the 1-bits before the start of this bucket.
+        let mut pos = if bucket == 0 {
+            0
         } else {
-            64 + self.high_boundaries[1].select_in_word(idx - p)
+            self.select(bucket - 1, false) + 1
         };
+        let mut i = pos - bucket;
+        // Walk the values in the bucket itself.
+        let low = (x & 0xff) as u8;
+        while i < n && self.high_boundaries[pos / 64] & (1 << (pos % 64)) != 0 {
+            if self.low_bits[i] >= low {
+                break;
+            }
+            i += 1;
+            pos += 1;
+        }
+        i
+    }
 
-        256 * self.reduced_offset as u64 + 256 * (one_pos - idx) as u64 + self.low_bits[idx] as u64
+    /// Walk the 1-bits of `high_boundaries` instead of selecting each of them.
+    fn decode(&self, out: &mut [u64]) {
+        let mut i = 0;
+        for (w, &word) in self.high_boundaries.iter().enumerate() {
+            let mut word = word;
+            while word != 0 && i < out.len() {
+                let one_pos = 64 * w + word.trailing_zeros() as usize;
+                out[i] = 256 * self.reduced_offset
+                    + 256 * (one_pos - i) as u64
+                    + self.low_bits[i] as u64;
+                word &= word - 1;
+                i += 1;
+            }
+        }
     }
 }
 
-/// Prefetch the given cacheline into L1 cache.
-fn prefetch_index<T>(s: &[T], index: usize) {
-    let ptr = unsafe { s.as_ptr().add(index) as *const u64 };
+/// The cache level that [`EfVec::prefetch_with`] fetches a cacheline into.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locality {
+    /// Fetch into all levels of the cache hierarchy.
+    #[default]
+    L1,
+    /// Fetch into L2 and higher.
+    L2,
+    /// Fetch into L3 and higher.
+    L3,
+    /// Fetch close to the processor, but minimize pollution of the caches,
+    /// for data that is only accessed once.
+    NonTemporal,
+}
+
+/// Prefetch the given cacheline into the given cache level.
+///
+/// On aarch64, this requires the `aarch64-prefetch` feature, and is a no-op otherwise.
+///
+/// `index` may be out of bounds, e.g. for indices passed to batched queries
+/// before they are checked. Prefetching an invalid address does not fault.
+#[inline(always)]
+fn prefetch_index<T>(s: &[T], index: usize, locality: Locality) {
+    let ptr = s.as_ptr().wrapping_add(index) as *const u64;
     #[cfg(target_arch = "x86_64")]
     unsafe {
-        std::arch::x86_64::_mm_prefetch(ptr as *const i8, std::arch::x86_64::_MM_HINT_T0);
+        use std::arch::x86_64::*;
+        match locality {
+            Locality::L1 => _mm_prefetch(ptr as *const i8, _MM_HINT_T0),
+            Locality::L2 => _mm_prefetch(ptr as *const i8, _MM_HINT_T1),
+            Locality::L3 => _mm_prefetch(ptr as *const i8, _MM_HINT_T2),
+            Locality::NonTemporal => _mm_prefetch(ptr as *const i8, _MM_HINT_NTA),
+        }
     }
     #[cfg(target_arch = "x86")]
     unsafe {
-        std::arch::x86::_mm_prefetch(ptr as *const i8, std::arch::x86::_MM_HINT_T0);
+        use std::arch::x86::*;
+        match locality {
+            Locality::L1 => _mm_prefetch(ptr as *const i8, _MM_HINT_T0),
+            Locality::L2 => _mm_prefetch(ptr as *const i8, _MM_HINT_T1),
+            Locality::L3 => _mm_prefetch(ptr as *const i8, _MM_HINT_T2),
+            Locality::NonTemporal => _mm_prefetch(ptr as *const i8, _MM_HINT_NTA),
+        }
     }
-    #[cfg(target_arch = "aarch64")]
+    // `std::arch::aarch64::_prefetch` is unstable, so use `prfm` directly.
+    #[cfg(all(target_arch = "aarch64", feature = "aarch64-prefetch"))]
     unsafe {
-        // TODO: Put this behind a feature flag.
-        // std::arch::aarch64::_prefetch(ptr as *const i8, std::arch::aarch64::_PREFETCH_LOCALITY3);
+        use std::arch::asm;
+        match locality {
+            Locality::L1 => {
+                asm!("prfm pldl1keep, [{}]", in(reg) ptr, options(nostack, preserves_flags, readonly))
+            }
+            Locality::L2 => {
+                asm!("prfm pldl2keep, [{}]", in(reg) ptr, options(nostack, preserves_flags, readonly))
+            }
+            Locality::L3 => {
+                asm!("prfm pldl3keep, [{}]", in(reg) ptr, options(nostack, preserves_flags, readonly))
+            }
+            Locality::NonTemporal => {
+                asm!("prfm pldl1strm, [{}]", in(reg) ptr, options(nostack, preserves_flags, readonly))
+            }
+        }
     }
-    #[cfg(not(any(target_arch = "x86_64", target_arch = "x86", target_arch = "aarch64")))]
+    #[cfg(not(any(
+        target_arch = "x86_64",
+        target_arch = "x86",
+        all(target_arch = "aarch64", feature = "aarch64-prefetch")
+    )))]
     {
         // Do nothing.
+        let _ = (ptr, locality);
+    }
+}
+
+/// `n` sorted values starting at `start`, with random gaps below `max_gap`.
+#[cfg(test)]
+fn random_vals(n: usize, start: u64, max_gap: u64) -> Vec<u64> {
+    let mut vals = Vec::with_capacity(n);
+    let mut v = start;
+    for _ in 0..n {
+        v += rand::random::<u64>() % max_gap;
+        vals.push(v);
+    }
+    vals
+}
+
+/// `n` sorted values with gaps below `max_gap`, except for the first 5% of
+/// every `period` values, whose gaps of up to 100000 cause escaped chunks.
+#[cfg(test)]
+fn test_vals(n: usize, period: usize, max_gap: u64) -> Vec<u64> {
+    let mut vals = Vec::with_capacity(n);
+    let mut v = 0u64;
+    for i in 0..n {
+        v += if i % period < period / 20 {
+            rand::random::<u64>() % 100000
+        } else {
+            rand::random::<u64>() % max_gap
+        };
+        vals.push(v);
     }
+    vals
 }
 
 #[test]
@@ -171,9 +908,339 @@ fn test() {
         }
         vals.sort_unstable();
 
-        let lef = CachelineEf::new(&vals);
+        let lef = CachelineEf::try_new(&vals, 0).unwrap();
         for i in 0..L {
             assert_eq!(lef.get(i), vals[i], "error; full list: {:?}", vals);
         }
     }
 }
+
+#[test]
+fn try_new_errors() {
+    let vals: Vec<u64> = (0..100).map(|i| i * 100).collect();
+    assert_eq!(CachelineEfVec::try_new(&vals).unwrap().len(), 100);
+
+    let mut unsorted = vals.clone();
+    unsorted.swap(50, 51);
+    assert_eq!(
+        CachelineEfVec::try_new(&unsorted).err(),
+        Some(CachelineEfError::NotSorted {
+            chunk: 1,
+            index: 51,
+            prev: 5100,
+            value: 5000
+        })
+    );
+    // The boundary between two chunks is checked as well.
+    let mut unsorted = vals.clone();
+    unsorted[L] = 0;
+    assert_eq!(
+        CachelineEfVec::try_new(&unsorted).err().unwrap().chunk(),
+        Some(1)
+    );
+
+    // Chunks that do not fit are escaped by `CachelineEfVec`.
+    let wide: Vec<u64> = (0..100).map(|i| i * 1000).collect();
+    assert!(matches!(
+        CachelineEf::try_new(&wide[..L], 0),
+        Err(CachelineEfError::RangeTooLarge { chunk: 0, .. })
+    ));
+
+    // Single chunks check that their values are sorted.
+    assert_eq!(
+        CachelineEf::try_new(&[300, 0], 2).err(),
+        Some(CachelineEfError::NotSorted {
+            chunk: 2,
+            index: 2 * L + 1,
+            prev: 300,
+            value: 0
+        })
+    );
+    assert!(matches!(
+        CachelineEf::try_new(&[0, 300, 100], 0),
+        Err(CachelineEfError::NotSorted { index: 2, .. })
+    ));
+    assert!(matches!(
+        crate::CachelineEfWide::try_new(&[300, 0], 0),
+        Err(CachelineEfError::NotSorted { index: 1, .. })
+    ));
+    assert!(matches!(
+        PackedEf::<4>::try_new(&[300, 0], 0),
+        Err(CachelineEfError::NotSorted { index: 1, .. })
+    ));
+
+    // Too many values are an error, also when validating.
+    assert_eq!(
+        CachelineEf::try_new(&vals[..L + 1], 3).err(),
+        Some(CachelineEfError::TooManyValues {
+            chunk: 3,
+            len: L + 1,
+            max: L
+        })
+    );
+    assert!(matches!(
+        crate::CachelineEfWide::try_new(&vals[..60], 0),
+        Err(CachelineEfError::TooManyValues { len: 60, .. })
+    ));
+    let packed = PackedEf::<4>::try_new(&[1], 0).unwrap();
+    assert!(matches!(
+        packed.validate(PackedEf::<4>::L + 1, 0),
+        Err(CachelineEfError::TooManyValues { .. })
+    ));
+    let lef = CachelineEf::try_new(&[1], 0).unwrap();
+    assert!(matches!(
+        lef.validate(L + 1, 0),
+        Err(CachelineEfError::TooManyValues { .. })
+    ));
+
+    let large = [1 << 40];
+    assert_eq!(
+        CachelineEf::try_new(&large, 0).err(),
+        Some(CachelineEfError::ValueTooLarge {
+            chunk: 0,
+            value: 1 << 40,
+            bound: 1 << 40
+        })
+    );
+}
+
+#[test]
+fn next_geq() {
+    for max_gap in [1, 3, 100, 400] {
+        let vals = random_vals(10000, rand::random::<u64>() % (1 << 39), max_gap);
+        let ef = CachelineEfVec::new(&vals);
+        for _ in 0..10000 {
+            let x = vals[0].saturating_sub(10)
+                + rand::random::<u64>() % (vals[vals.len() - 1] - vals[0] + 20);
+            let pos = vals.partition_point(|&v| v < x);
+            assert_eq!(ef.next_geq(x), vals.get(pos).map(|&v| (pos, v)), "x = {x}");
+        }
+        for (i, &v) in vals.iter().enumerate() {
+            let pos = vals.partition_point(|&w| w < v);
+            assert_eq!(ef.next_geq(v), Some((pos, v)), "i = {i}");
+        }
+    }
+    assert_eq!(CachelineEfVec::new(&[]).next_geq(0), None);
+}
+
+#[test]
+fn prev_leq_and_rank() {
+    for max_gap in [1, 3, 100, 400] {
+        let vals = random_vals(10000, rand::random::<u64>() % (1 << 39), max_gap);
+        let ef = CachelineEfVec::new(&vals);
+        for _ in 0..10000 {
+            let x = vals[0].saturating_sub(10)
+                + rand::random::<u64>() % (vals[vals.len() - 1] - vals[0] + 20);
+            let rank = vals.partition_point(|&v| v < x);
+            assert_eq!(ef.rank(x), rank, "x = {x}");
+            let leq = vals.partition_point(|&v| v <= x);
+            let prev = leq.checked_sub(1).map(|pos| (pos, vals[pos]));
+            assert_eq!(ef.prev_leq(x), prev, "x = {x}");
+        }
+    }
+    let ef = CachelineEfVec::new(&[0, 5, 5, 5, 20000]);
+    assert_eq!(ef.rank(0), 0);
+    assert_eq!(ef.rank(5), 1);
+    assert_eq!(ef.rank(6), 4);
+    assert_eq!(ef.rank(u64::MAX), 5);
+    assert_eq!(ef.prev_leq(5), Some((3, 5)));
+    assert_eq!(ef.prev_leq(u64::MAX), Some((4, 20000)));
+    assert_eq!(CachelineEfVec::new(&[]).prev_leq(u64::MAX), None);
+}
+
+#[test]
+fn escaped_chunks() {
+    // Mix dense stretches with large gaps and values beyond 2^40.
+    let mut vals = vec![];
+    let mut v = 0u64;
+    for i in 0..10000 {
+        v += match i % 1000 {
+            0..=99 => 1 << (i % 41),
+            100..=199 => rand::random::<u64>() % 2000,
+            _ => rand::random::<u64>() % 100,
+        };
+        vals.push(v);
+    }
+    vals.extend([u64::MAX - 1, u64::MAX, u64::MAX]);
+    let ef = CachelineEfVec::new(&vals);
+    assert!(ef.size_in_bytes() > 64 * vals.len().div_ceil(L));
+    for (i, &v) in vals.iter().enumerate() {
+        assert_eq!(ef.index(i), v, "i = {i}");
+    }
+    for _ in 0..10000 {
+        let x = vals[rand::random::<usize>() % vals.len()]
+            .wrapping_add(rand::random::<u64>() % 3)
+            .wrapping_sub(1);
+        let rank = vals.partition_point(|&v| v < x);
+        assert_eq!(ef.rank(x), rank, "x = {x}");
+        assert_eq!(ef.next_geq(x), vals.get(rank).map(|&v| (rank, v)));
+        let leq = vals.partition_point(|&v| v <= x);
+        assert_eq!(ef.prev_leq(x), leq.checked_sub(1).map(|p| (p, vals[p])));
+    }
+    assert_eq!(ef.rank(u64::MAX), vals.len() - 2);
+    assert_eq!(ef.prev_leq(u64::MAX), Some((vals.len() - 1, u64::MAX)));
+}
+
+#[test]
+fn prefetch() {
+    let vals: Vec<u64> = (0..1000).map(|i| i * 100).collect();
+    let ef = CachelineEfVec::new(&vals);
+    for locality in [
+        Locality::L1,
+        Locality::L2,
+        Locality::L3,
+        Locality::NonTemporal,
+    ] {
+        for i in 0..vals.len() {
+            ef.prefetch_with(i, locality);
+        }
+        // Out of bounds indices are not dereferenced.
+        ef.prefetch_with(usize::MAX, locality);
+    }
+}
+
+#[test]
+fn from_bytes() {
+    let vals: Vec<u64> = (0..1000).map(|i| i * 100 + i % 7).collect();
+    let ef = CachelineEfVec::new(&vals);
+    let bytes = ef.as_bytes();
+    assert_eq!(bytes.len(), ef.size_in_bytes());
+    let view = CachelineEfVec::<&[CachelineEf]>::from_bytes(bytes, vals.len()).unwrap();
+    assert!(view.iter().eq(vals.iter().copied()));
+
+    assert_eq!(
+        CachelineEfVec::<&[CachelineEf]>::from_bytes(&bytes[8..], vals.len()).err(),
+        Some(CachelineEfError::Misaligned { align: 64 })
+    );
+    assert_eq!(
+        CachelineEfVec::<&[CachelineEf]>::from_bytes(&bytes[..bytes.len() - 8], vals.len()).err(),
+        Some(CachelineEfError::InvalidSize {
+            size: bytes.len() - 8,
+            chunk_size: 64
+        })
+    );
+    assert_eq!(
+        CachelineEfVec::<&[CachelineEf]>::from_bytes(bytes, vals.len() + 100).err(),
+        Some(CachelineEfError::TooFewChunks {
+            len: vals.len() + 100,
+            expected: 25,
+            found: 23
+        })
+    );
+
+    let mut corrupted = ef.clone();
+    corrupted.ef[3].high_boundaries[1] ^= 1 << 63;
+    assert_eq!(
+        CachelineEfVec::<&[CachelineEf]>::from_bytes(corrupted.as_bytes(), vals.len()).err(),
+        Some(CachelineEfError::WrongLength {
+            chunk: 3,
+            expected: L,
+            found: L + 1
+        })
+    );
+
+    let mut corrupted = CachelineEfWideVec::new(&vals);
+    corrupted.ef[2].reduced_offset = u64::MAX / 256;
+    assert_eq!(
+        CachelineEfWideVec::<&[CachelineEfWide]>::from_bytes(corrupted.as_bytes(), vals.len())
+            .err(),
+        Some(CachelineEfError::DecodeOverflow { chunk: 2 })
+    );
+}
+
+#[test]
+fn validate() {
+    let mut vals: Vec<u64> = (0..1000).map(|i| i * 100 + i % 7).collect();
+    vals.extend((1..100).map(|i| 100_000 + i * 100_000));
+    let ef = CachelineEfVec::new(&vals);
+    assert_eq!(ef.validate(), Ok(()));
+    assert_eq!(CachelineEfVec::new(&[]).validate(), Ok(()));
+
+    // Chunks in the wrong order.
+    let mut corrupted = ef.clone();
+    corrupted.ef.swap(3, 4);
+    assert_eq!(
+        corrupted.validate(),
+        Err(CachelineEfError::NotSorted {
+            chunk: 4,
+            index: 4 * L,
+            prev: vals[5 * L - 1],
+            value: vals[3 * L]
+        })
+    );
+
+    // Values within a chunk in the wrong order.
+    let mut corrupted = ef.clone();
+    corrupted.ef[0].low_bits.swap(0, 1);
+    assert_eq!(corrupted.validate().err().unwrap().chunk(), Some(0));
+
+    // Values that are too large.
+    let mut corrupted = ef.clone();
+    let k = ef.len.div_ceil(L) - 1;
+    assert!(escaped(&corrupted.ef[k]).is_some());
+    corrupted.ef[5].reduced_offset = u32::MAX;
+    assert!(matches!(
+        corrupted.validate(),
+        Err(CachelineEfError::ValueTooLarge { chunk: 5, .. })
+    ));
+
+    // Escaped chunks pointing past the end.
+    let mut corrupted = ef.clone();
+    corrupted.ef[k] = from_words(&[0, 100, first(&ef.ef[k])]);
+    assert_eq!(
+        corrupted.validate(),
+        Err(CachelineEfError::EscapeOutOfBounds {
+            chunk: k,
+            block: 100,
+            num_blocks: ef.ef.len() - k - 1
+        })
+    );
+}
+
+#[test]
+fn line_sizes() {
+    fn check<C: EfChunk>(size: usize) {
+        assert_eq!(size_of::<C>(), size);
+        assert_eq!(align_of::<C>(), size);
+        // The line holds `W = (size - 4 - L) / 8` words of high bits.
+        let max = 256 * (64 * (size - 4 - C::L) / 8 - C::L) as u64;
+        let mut vals = vec![0u64; C::L];
+        for _ in 0..10000 {
+            let offset = rand::random::<u64>() % (1 << 39);
+            for v in &mut vals {
+                *v = offset + rand::random::<u64>() % max;
+            }
+            vals.sort_unstable();
+            let c = C::try_new(&vals, 0).unwrap();
+            for (i, &v) in vals.iter().enumerate() {
+                assert_eq!(c.get(i), v);
+            }
+        }
+        assert!(matches!(
+            C::try_new(&[0, max + 1], 0),
+            Err(CachelineEfError::RangeTooLarge { .. })
+        ));
+
+        let vals: Vec<u64> = (0..10000).map(|i| i * max / C::L as u64).collect();
+        let ef = EfVec::<C>::new(&vals);
+        assert_eq!(ef.size_in_bytes(), size * vals.len().div_ceil(C::L));
+        assert!(ef.iter().eq(vals.iter().copied()));
+        assert_eq!(ef.validate(), Ok(()));
+
+        let path = std::env::temp_dir().join(format!(
+            "cacheline-ef-lines-{size}-{}.bin",
+            std::process::id()
+        ));
+        ef.save(&path).unwrap();
+        assert!(EfVec::<C>::load(&path).unwrap().iter().eq(ef.iter()));
+        #[cfg(feature = "mmap")]
+        {
+            let mapped = unsafe { EfVec::<C, _>::mmap(&path).unwrap() };
+            assert!(mapped.iter().eq(ef.iter()));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+    check::<CachelineEf32>(32);
+    check::<CachelineEf>(64);
+    check::<CachelineEf128>(128);
+}
@@ -0,0 +1,355 @@
+use crate::{CachelineEf, CachelineEfError, EfChunk, EfVec, EfVecBuilder, Iter};
+use std::{iter::FusedIterator, ops::Range};
+
+/// Start offsets of variable-length records, stored as [`CachelineEf`] chunks.
+pub type CachelineEfOffsets<E = Vec<CachelineEf>> = EfOffsets<CachelineEf, E>;
+
+/// Builder for [`CachelineEfOffsets`].
+pub type CachelineEfOffsetsBuilder = EfOffsetsBuilder<CachelineEf>;
+
+/// The start offsets of `len` consecutive variable-length records, together
+/// with the end offset of the last record.
+///
+/// Each chunk stores `L - 1` start offsets followed by the first offset of
+/// the next chunk, so that [`Self::range`] and [`Self::len_of`] read the start
+/// and end of a record from the same cacheline, also at chunk boundaries. This
+/// costs one extra value per chunk.
+#[derive(Clone, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+pub struct EfOffsets<C, E = Vec<C>> {
+    // Chunk `k` holds `offsets[k*(L-1) ..= (k+1)*(L-1)]`, or fewer in the last chunk.
+    ef: EfVec<C, E>,
+}
+
+impl<C: EfChunk> EfOffsets<C> {
+    /// Store the `offsets.len() - 1` records `offsets[i]..offsets[i+1]`.
+    ///
+    /// Panics when the offsets are not sorted; see [`Self::try_new`].
+    pub fn new(offsets: &[u64]) -> Self {
+        Self::try_new(offsets).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Store the `offsets.len() - 1` records `offsets[i]..offsets[i+1]`, or
+    /// return an error explaining where the offsets are not sorted.
+    pub fn try_new(offsets: &[u64]) -> Result<Self, CachelineEfError> {
+        let mut builder = EfOffsetsBuilder::with_capacity(offsets.len());
+        for &offset in offsets {
+            builder.try_push(offset)?;
+        }
+        Ok(builder.finish())
+    }
+
+    /// Store records of the given lengths, placed back to back starting at offset 0.
+    pub fn from_lengths(lengths: &[u64]) -> Self {
+        let mut builder = EfOffsetsBuilder::with_capacity(lengths.len() + 1);
+        let mut offset = 0u64;
+        builder.push(offset);
+        for &l in lengths {
+            offset = offset
+                .checked_add(l)
+                .unwrap_or_else(|| panic!("Total length overflows u64."));
+            builder.push(offset);
+        }
+        builder.finish()
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> EfOffsets<C, E> {
+    /// The number of records.
+    pub fn len(&self) -> usize {
+        // Every chunk stores one offset that does not start a record in it.
+        self.ef.len() - self.ef.len().div_ceil(C::L)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn size_in_bytes(&self) -> usize {
+        self.ef.size_in_bytes()
+    }
+
+    /// The start and end offset of record `i`.
+    pub fn range(&self, i: usize) -> Range<u64> {
+        assert!(
+            i < self.len(),
+            "Index {i} out of bounds. Length is {}.",
+            self.len()
+        );
+        let (k, r) = (i / (C::L - 1), i % (C::L - 1));
+        self.ef.chunk_get(k, r)..self.ef.chunk_get(k, r + 1)
+    }
+
+    /// The length of record `i`.
+    pub fn len_of(&self, i: usize) -> u64 {
+        let range = self.range(i);
+        range.end - range.start
+    }
+
+    /// Iterate over the ranges of all records, decoding each chunk once.
+    pub fn iter(&self) -> OffsetsIter<'_, C, E> {
+        let len = self.len();
+        let mut offsets = self.ef.iter();
+        let (start, end) = match len {
+            0 => (0, 0),
+            _ => (offsets.next().unwrap(), offsets.next_back().unwrap()),
+        };
+        OffsetsIter {
+            offsets,
+            front: 1,
+            back: self.ef.len().saturating_sub(1),
+            start,
+            end,
+            len,
+        }
+    }
+
+    /// Check that the data is a valid encoding; see [`EfVec::validate`].
+    /// Also checks that the last offset of each chunk equals the first offset
+    /// of the next chunk.
+    pub fn validate(&self) -> Result<(), CachelineEfError> {
+        self.ef.validate()?;
+        for k in 1..self.ef.len().div_ceil(C::L) {
+            let expected = self.ef.chunk_get(k - 1, C::L - 1);
+            let found = self.ef.chunk_get(k, 0);
+            if expected != found {
+                return Err(CachelineEfError::OffsetMismatch {
+                    chunk: k,
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, C: EfChunk, E: AsRef<[C]>> IntoIterator for &'a EfOffsets<C, E> {
+    type Item = Range<u64>;
+    type IntoIter = OffsetsIter<'a, C, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the records of an [`EfOffsets`], returned by [`EfOffsets::iter`].
+///
+/// The offsets are read sequentially with an [`Iter`], skipping the first
+/// offset of each chunk after the first, which repeats the last offset of
+/// the previous chunk.
+pub struct OffsetsIter<'a, C, E> {
+    offsets: Iter<'a, C, E>,
+    /// Position of the next value returned by `offsets.next()`.
+    front: usize,
+    /// One past the position of the next value returned by `offsets.next_back()`.
+    back: usize,
+    /// The start of the next record returned by `next`.
+    start: u64,
+    /// The end of the next record returned by `next_back`.
+    end: u64,
+    /// The number of remaining records.
+    len: usize,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> Iterator for OffsetsIter<'_, C, E> {
+    type Item = Range<u64>;
+
+    fn next(&mut self) -> Option<Range<u64>> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // The end of the last record was already read from the back.
+        let end = if self.len == 0 {
+            self.end
+        } else {
+            loop {
+                let (v, pos) = (self.offsets.next().unwrap(), self.front);
+                self.front += 1;
+                if !pos.is_multiple_of(C::L) {
+                    break v;
+                }
+            }
+        };
+        let start = std::mem::replace(&mut self.start, end);
+        Some(start..end)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> DoubleEndedIterator for OffsetsIter<'_, C, E> {
+    fn next_back(&mut self) -> Option<Range<u64>> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // The start of the first record was already read from the front.
+        let start = if self.len == 0 {
+            self.start
+        } else {
+            loop {
+                let v = self.offsets.next_back().unwrap();
+                self.back -= 1;
+                if !self.back.is_multiple_of(C::L) {
+                    break v;
+                }
+            }
+        };
+        let end = std::mem::replace(&mut self.end, start);
+        Some(start..end)
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> ExactSizeIterator for OffsetsIter<'_, C, E> {}
+
+impl<C: EfChunk, E: AsRef<[C]>> FusedIterator for OffsetsIter<'_, C, E> {}
+
+/// Encodes a stream of non-decreasing offsets into an [`EfOffsets`], without
+/// first collecting them into a slice.
+///
+/// The offsets are pushed into an [`EfVecBuilder`], repeating the last offset
+/// of each full chunk as the first offset of the next.
+pub struct EfOffsetsBuilder<C> {
+    ef: EfVecBuilder<C>,
+    /// The number of offsets pushed so far.
+    len: usize,
+    last: u64,
+}
+
+impl<C: EfChunk> Default for EfOffsetsBuilder<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: EfChunk> EfOffsetsBuilder<C> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a builder with space for `n` offsets.
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            ef: EfVecBuilder::with_capacity(n + n / (C::L - 1)),
+            len: 0,
+            last: 0,
+        }
+    }
+
+    /// The number of records so far, one less than the number of offsets.
+    pub fn num_records(&self) -> usize {
+        self.len.saturating_sub(1)
+    }
+
+    /// Append an offset.
+    ///
+    /// Panics when `offset` is smaller than the previous offset; see [`Self::try_push`].
+    pub fn push(&mut self, offset: u64) {
+        self.try_push(offset).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Append an offset, or return an error when it is smaller than the
+    /// previous offset. In that case, the builder is left unchanged.
+    pub fn try_push(&mut self, offset: u64) -> Result<(), CachelineEfError> {
+        if self.len > 0 {
+            if offset < self.last {
+                return Err(CachelineEfError::NotSorted {
+                    chunk: (self.len - 1) / (C::L - 1),
+                    index: self.len,
+                    prev: self.last,
+                    value: offset,
+                });
+            }
+            if self.ef.len().is_multiple_of(C::L) {
+                // The previous offset ends a full chunk and also starts the next.
+                self.ef.push(self.last);
+            }
+        }
+        self.ef.push(offset);
+        self.len += 1;
+        self.last = offset;
+        Ok(())
+    }
+
+    /// Encode the remaining offsets and return the final [`EfOffsets`].
+    pub fn finish(self) -> EfOffsets<C> {
+        EfOffsets {
+            ef: self.ef.finish(),
+        }
+    }
+}
+
+#[test]
+fn offsets() {
+    for max_len in [1, 10, 100, 10000] {
+        for n in [0, 1, 42, 43, 44, 86, 1000] {
+            let lengths: Vec<u64> = (0..n).map(|_| rand::random::<u64>() % max_len).collect();
+            let offsets = CachelineEfOffsets::from_lengths(&lengths);
+            assert_eq!(offsets.len(), n);
+            assert_eq!(offsets.validate(), Ok(()));
+            let mut start = 0;
+            for (i, &l) in lengths.iter().enumerate() {
+                assert_eq!(offsets.range(i), start..start + l);
+                assert_eq!(offsets.len_of(i), l);
+                start += l;
+            }
+            assert!(offsets
+                .iter()
+                .map(|r| r.end - r.start)
+                .eq(lengths.iter().copied()));
+            assert!((&offsets)
+                .into_iter()
+                .rev()
+                .eq((0..n).rev().map(|i| offsets.range(i))));
+            assert_eq!(offsets.iter().len(), n);
+            // Alternate between both ends.
+            let mut it = offsets.iter();
+            for i in 0..n / 2 {
+                assert_eq!(it.next(), Some(offsets.range(i)));
+                assert_eq!(it.next_back(), Some(offsets.range(n - 1 - i)));
+            }
+            assert_eq!(it.next(), (n % 2 == 1).then(|| offsets.range(n / 2)));
+            assert_eq!(it.next(), None);
+        }
+    }
+
+    let offsets = CachelineEfOffsets::new(&[5, 5, 7]);
+    assert_eq!(offsets.range(0), 5..5);
+    assert_eq!(offsets.range(1), 5..7);
+    assert_eq!(CachelineEfOffsets::new(&[]).len(), 0);
+    assert_eq!(
+        CachelineEfOffsets::try_new(&[1, 3, 2]).err(),
+        Some(CachelineEfError::NotSorted {
+            chunk: 0,
+            index: 2,
+            prev: 3,
+            value: 2
+        })
+    );
+
+    let offsets: Vec<u64> = (0..1000).map(|i| 3 * i).collect();
+    let mut builder = CachelineEfOffsetsBuilder::new();
+    for &offset in &offsets {
+        builder.push(offset);
+    }
+    assert_eq!(builder.num_records(), 999);
+    let streamed = builder.finish();
+    assert_eq!(streamed.validate(), Ok(()));
+    assert!(streamed.iter().eq(CachelineEfOffsets::new(&offsets).iter()));
+
+    // Chunk 1 does not start with the last offset of chunk 0.
+    let vals: Vec<u64> = (0..88).collect();
+    let offsets = CachelineEfOffsets {
+        ef: EfVec::new(&vals),
+    };
+    assert_eq!(
+        offsets.validate(),
+        Err(CachelineEfError::OffsetMismatch {
+            chunk: 1,
+            expected: 43,
+            found: 44
+        })
+    );
+}
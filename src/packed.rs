@@ -0,0 +1,255 @@
+use crate::{check_chunk, CachelineEfError, EfChunk, EfVec};
+use common_traits::SelectInWord;
+
+/// Number of bits used for the high and low parts. The last 32 bits hold the offset.
+const BITS: usize = 480;
+
+/// A list of values encoded in [`PackedEf`] chunks with `B` low bits per value.
+pub type PackedEfVec<const B: usize, E = Vec<PackedEf<B>>> = EfVec<PackedEf<B>, E>;
+
+/// Single-cacheline Elias-Fano encoding with a configurable number of `B` low bits per value.
+///
+/// Each chunk holds `L = 480 / (B + 3)` values. The remaining `H = 480 - L*B`
+/// bits store the high parts in unary, so that a chunk can span a range of
+/// `(H - L) * 2^B`, at least `2 * L * 2^B`. Values must be less than `2^(32+B)`.
+///
+/// `B` should be close to log2 of the average difference between consecutive values:
+/// - `PackedEf<3>` holds 80 values in a range of 160*8=1280, using 6.4 bits/value,
+/// - `PackedEf<8>` holds 43 values in a range of 93*256=23808, similar to [`CachelineEf`](crate::CachelineEf),
+/// - `PackedEf<12>` holds 32 values in a range of 64*4096=262144, using 16 bits/value.
+// Like `CachelineEf`, this has size 64 bytes and is aligned to 64 bytes.
+#[derive(Clone, Copy, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[repr(C)]
+#[repr(align(64))]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+#[cfg_attr(feature = "epserde", zero_copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[copy_type]
+pub struct PackedEf<const B: usize> {
+    // Bits `0..H` store the high parts in unary, like `high_boundaries` in `CachelineEf`.
+    // Bits `H..480` store the low `B` bits of each value.
+    // Bits `480..512` store the offset of the first value, shifted right by `B`.
+    words: [u64; 8],
+}
+
+impl<const B: usize> PackedEf<B> {
+    /// Number of bits of the high part.
+    const H: usize = BITS - <Self as EfChunk>::L * B;
+    const LOW_MASK: u64 = (1 << B) - 1;
+    const CHECK: () = assert!(B <= 32, "At most 32 low bits are supported.");
+
+    fn offset(&self) -> u64 {
+        self.words[7] >> 32
+    }
+
+    fn bit(&self, pos: usize) -> bool {
+        self.words[pos / 64] & (1 << (pos % 64)) != 0
+    }
+
+    fn low(&self, idx: usize) -> u64 {
+        let pos = Self::H + idx * B;
+        let (w, o) = (pos / 64, pos % 64);
+        let mut low = self.words[w] >> o;
+        if o + B > 64 {
+            low |= self.words[w + 1] << (64 - o);
+        }
+        low & Self::LOW_MASK
+    }
+
+    /// The bits of word `w` that belong to the high part.
+    fn high_mask(w: usize) -> u64 {
+        match Self::H.saturating_sub(64 * w) {
+            0 => 0,
+            bits @ 1..=63 => (1 << bits) - 1,
+            _ => u64::MAX,
+        }
+    }
+
+    /// Position of the `k`'th 1-bit (or 0-bit) in the high part.
+    fn select(&self, mut k: usize, ones: bool) -> usize {
+        for w in 0..Self::H.div_ceil(64) {
+            let word = if ones { self.words[w] } else { !self.words[w] } & Self::high_mask(w);
+            let cnt = word.count_ones() as usize;
+            if k < cnt {
+                return 64 * w + word.select_in_word(k);
+            }
+            k -= cnt;
+        }
+        unreachable!("Not enough bits set in the high part.")
+    }
+}
+
+// SAFETY: `PackedEf` is 8 words, and bit 0 is set for the first value.
+unsafe impl<const B: usize> EfChunk for PackedEf<B> {
+    const L: usize = BITS / (B + 3);
+    const MAX_RANGE: u64 = ((Self::H - Self::L) as u64) << B;
+    const TAG: u64 = 3 << 56 | B as u64;
+
+    fn try_new(vals: &[u64], chunk: usize) -> Result<Self, CachelineEfError> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CHECK;
+        check_chunk::<Self>(vals, chunk)?;
+        let l = vals.len();
+        if B < 32 && vals[l - 1] >> (32 + B) != 0 {
+            return Err(CachelineEfError::ValueTooLarge {
+                chunk,
+                value: vals[l - 1],
+                bound: 1 << (32 + B),
+            });
+        }
+        let offset = vals[0] >> B;
+        if (vals[l - 1] >> B) - offset > (Self::H - Self::L) as u64 {
+            return Err(CachelineEfError::RangeTooLarge {
+                chunk,
+                first: vals[0],
+                last: vals[l - 1],
+                max_range: Self::MAX_RANGE,
+            });
+        }
+
+        let mut words = [0u64; 8];
+        for (i, &v) in vals.iter().enumerate() {
+            let idx = i + ((v >> B) - offset) as usize;
+            words[idx / 64] |= 1 << (idx % 64);
+
+            let low = v & Self::LOW_MASK;
+            let pos = Self::H + i * B;
+            let (w, o) = (pos / 64, pos % 64);
+            words[w] |= low << o;
+            if o + B > 64 {
+                words[w + 1] |= low >> (64 - o);
+            }
+        }
+        words[7] |= offset << 32;
+        Ok(Self { words })
+    }
+
+    fn get(&self, idx: usize) -> u64 {
+        let one_pos = self.select(idx, true);
+        ((self.offset() + (one_pos - idx) as u64) << B) | self.low(idx)
+    }
+
+    fn len(&self) -> usize {
+        (0..Self::H.div_ceil(64))
+            .map(|w| (self.words[w] & Self::high_mask(w)).count_ones() as usize)
+            .sum()
+    }
+
+    fn first(&self) -> u64 {
+        (self.offset() << B) | self.low(0)
+    }
+
+    fn rank(&self, x: u64) -> usize {
+        let n = self.len();
+        let Some(bucket) = (x >> B).checked_sub(self.offset()) else {
+            return 0;
+        };
+        if bucket > (Self::H - n) as u64 {
+            return n;
+        }
+        let bucket = bucket as usize;
+        let mut pos = if bucket == 0 {
+            0
+        } else {
+            self.select(bucket - 1, false) + 1
+        };
+        let mut i = pos - bucket;
+        let low = x & Self::LOW_MASK;
+        while i < n && self.bit(pos) && self.low(i) < low {
+            i += 1;
+            pos += 1;
+        }
+        i
+    }
+
+    fn unused_high_bits(&self) -> usize {
+        let used = (0..Self::H.div_ceil(64))
+            .rev()
+            .find_map(|w| {
+                let word = self.words[w] & Self::high_mask(w);
+                (word != 0).then(|| 64 * w + 64 - word.leading_zeros() as usize)
+            })
+            .unwrap_or(0);
+        Self::H - used
+    }
+
+    fn validate(&self, len: usize, chunk: usize) -> Result<(), CachelineEfError> {
+        if len == 0 {
+            return Err(CachelineEfError::EmptyChunk { chunk });
+        }
+        if len > Self::L {
+            return Err(CachelineEfError::TooManyValues {
+                chunk,
+                len,
+                max: Self::L,
+            });
+        }
+        let found = self.len();
+        if found != len {
+            return Err(CachelineEfError::WrongLength {
+                chunk,
+                expected: len,
+                found,
+            });
+        }
+        if !self.bit(0) {
+            return Err(CachelineEfError::MissingFirstBit { chunk });
+        }
+        // The high part of the last value must fit in `64 - B` bits.
+        let high = self.offset() + (self.select(len - 1, true) - (len - 1)) as u64;
+        if B > 0 && high >> (64 - B) != 0 {
+            return Err(CachelineEfError::DecodeOverflow { chunk });
+        }
+        let last = self.get(len - 1);
+        if B < 32 && last >> (32 + B) != 0 {
+            return Err(CachelineEfError::ValueTooLarge {
+                chunk,
+                value: last,
+                bound: 1 << (32 + B),
+            });
+        }
+        Ok(())
+    }
+
+    fn decode(&self, out: &mut [u64]) {
+        let mut i = 0;
+        for w in 0..Self::H.div_ceil(64) {
+            let mut word = self.words[w] & Self::high_mask(w);
+            while word != 0 && i < out.len() {
+                let one_pos = 64 * w + word.trailing_zeros() as usize;
+                out[i] = ((self.offset() + (one_pos - i) as u64) << B) | self.low(i);
+                word &= word - 1;
+                i += 1;
+            }
+        }
+    }
+}
+
+#[test]
+fn packed() {
+    fn check<const B: usize>(max_gap: u64, start: u64) {
+        let vals = crate::random_vals(5000, start, max_gap);
+        let ef = PackedEfVec::<B>::new(&vals);
+        for (i, &v) in vals.iter().enumerate() {
+            assert_eq!(ef.index(i), v, "B = {B}, i = {i}");
+        }
+        for _ in 0..5000 {
+            let x = vals[rand::random::<usize>() % vals.len()] + rand::random::<u64>() % 3 - 1;
+            let rank = vals.partition_point(|&v| v < x);
+            assert_eq!(ef.rank(x), rank, "B = {B}, x = {x}");
+            assert_eq!(ef.next_geq(x), vals.get(rank).map(|&v| (rank, v)));
+            let leq = vals.partition_point(|&v| v <= x);
+            assert_eq!(ef.prev_leq(x), leq.checked_sub(1).map(|p| (p, vals[p])));
+        }
+    }
+    check::<0>(3, 1);
+    check::<3>(16, 1);
+    check::<8>(400, 1);
+    // Values close to 2^(32+B).
+    check::<8>(400, (1 << 40) - 1_000_000);
+    check::<12>(8192, 1);
+    check::<20>(1 << 21, 1);
+    check::<32>(1 << 33, u64::MAX / 2);
+    assert_eq!(<PackedEf<8> as EfChunk>::L, 43);
+    assert_eq!(PackedEf::<8>::H, 136);
+}
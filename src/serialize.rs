@@ -0,0 +1,146 @@
+//! `serde` support. Deserializing an [`EfVec`] checks that its chunks are
+//! consistent with its length, so that queries on it can not panic or read
+//! out of bounds.
+
+use crate::{Alignment, CachelineEfWide, EfChunk, EfLine, EfVec};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::marker::PhantomData;
+
+/// Serialized form of [`EfLine`] and [`CachelineEfWide`], since serde does
+/// not support arrays of more than 32 or a generic number of elements.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ChunkRepr<O> {
+    high_boundaries: Vec<u64>,
+    reduced_offset: O,
+    low_bits: Vec<u8>,
+}
+
+impl<const W: usize, const N: usize, A: Alignment> From<EfLine<W, N, A>> for ChunkRepr<u32> {
+    fn from(c: EfLine<W, N, A>) -> Self {
+        Self {
+            high_boundaries: c.high_boundaries.to_vec(),
+            reduced_offset: c.reduced_offset,
+            low_bits: c.low_bits.to_vec(),
+        }
+    }
+}
+
+impl<const W: usize, const N: usize, A: Alignment> TryFrom<ChunkRepr<u32>> for EfLine<W, N, A> {
+    type Error = String;
+
+    fn try_from(r: ChunkRepr<u32>) -> Result<Self, String> {
+        Ok(Self {
+            high_boundaries: high_boundaries(r.high_boundaries)?,
+            reduced_offset: r.reduced_offset,
+            low_bits: r.low_bits.try_into().map_err(|low_bits: Vec<u8>| {
+                format!("Expected {N} low bytes, found {}", low_bits.len())
+            })?,
+            _align: [],
+        })
+    }
+}
+
+impl From<CachelineEfWide> for ChunkRepr<u64> {
+    fn from(c: CachelineEfWide) -> Self {
+        Self {
+            high_boundaries: c.high_boundaries.to_vec(),
+            reduced_offset: c.reduced_offset,
+            low_bits: c.low_bits.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<ChunkRepr<u64>> for CachelineEfWide {
+    type Error = String;
+
+    fn try_from(r: ChunkRepr<u64>) -> Result<Self, String> {
+        Ok(Self {
+            high_boundaries: high_boundaries(r.high_boundaries)?,
+            reduced_offset: r.reduced_offset,
+            low_bits: r.low_bits.try_into().map_err(|low_bits: Vec<u8>| {
+                format!("Expected {} low bytes, found {}", Self::L, low_bits.len())
+            })?,
+        })
+    }
+}
+
+fn high_boundaries<const W: usize>(words: Vec<u64>) -> Result<[u64; W], String> {
+    words
+        .try_into()
+        .map_err(|words: Vec<u64>| format!("Expected {W} high words, found {}", words.len()))
+}
+
+#[derive(Serialize)]
+struct EfVecRef<'a, C> {
+    len: usize,
+    ef: &'a [C],
+}
+
+#[derive(Deserialize)]
+struct EfVecOwned<C> {
+    len: usize,
+    ef: Vec<C>,
+}
+
+impl<C: EfChunk + Serialize, E: AsRef<[C]>> Serialize for EfVec<C, E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        EfVecRef {
+            len: self.len,
+            ef: self.ef.as_ref(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, C: EfChunk + Deserialize<'de>> Deserialize<'de> for EfVec<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let EfVecOwned::<C> { len, ef } = EfVecOwned::deserialize(deserializer)?;
+        let ef = Self {
+            ef,
+            len,
+            _chunk: PhantomData,
+        };
+        ef.validate().map_err(D::Error::custom)?;
+        Ok(ef)
+    }
+}
+
+#[test]
+fn serde_roundtrip() {
+    fn check<C: EfChunk + Serialize + for<'de> Deserialize<'de>>(vals: &[u64]) {
+        let ef = EfVec::<C>::new(vals);
+        let bytes = bincode::serialize(&ef).unwrap();
+        let ef2: EfVec<C> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(ef2.len(), vals.len());
+        assert!(ef2.iter().eq(vals.iter().copied()));
+        assert_eq!(bytes, bincode::serialize(&ef2).unwrap());
+
+        // A truncated list of chunks is rejected.
+        let mut truncated: EfVecOwned<C> = bincode::deserialize(&bytes).unwrap();
+        truncated.ef.pop();
+        let bytes = bincode::serialize(&EfVecRef {
+            len: truncated.len,
+            ef: &truncated.ef,
+        })
+        .unwrap();
+        assert!(bincode::deserialize::<EfVec<C>>(&bytes).is_err());
+    }
+
+    let vals = crate::test_vals(1000, 100, 100);
+    check::<crate::CachelineEf>(&vals);
+    check::<crate::CachelineEf128>(&vals);
+    check::<CachelineEfWide>(&vals);
+    check::<crate::PackedEf<6>>(&vals);
+
+    // A chunk with a wrong number of 1-bits is rejected.
+    let ef = crate::CachelineEfVec::new(&vals[..100]);
+    let mut r: EfVecOwned<ChunkRepr<u32>> =
+        bincode::deserialize(&bincode::serialize(&ef).unwrap()).unwrap();
+    r.ef[1].high_boundaries[1] ^= 1 << 63;
+    let bytes = bincode::serialize(&EfVecRef {
+        len: r.len,
+        ef: &r.ef,
+    })
+    .unwrap();
+    assert!(bincode::deserialize::<crate::CachelineEfVec>(&bytes).is_err());
+}
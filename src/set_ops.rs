@@ -0,0 +1,286 @@
+//! Set operations on sorted vectors, treating each vector as the set of its
+//! distinct values.
+//!
+//! The results are iterators over distinct, increasing values, which can be
+//! encoded again by collecting them into an [`EfVec`].
+
+use crate::{EfChunk, EfVec, Iter};
+use std::{cmp::Reverse, collections::BinaryHeap, iter::FusedIterator};
+
+/// A forward iterator over an [`EfVec`] that can skip ahead.
+struct Cursor<'a, C, E> {
+    ef: &'a EfVec<C, E>,
+    iter: Iter<'a, C, E>,
+    /// The current value, or `None` at the end.
+    cur: Option<u64>,
+}
+
+impl<'a, C: EfChunk, E: AsRef<[C]>> Cursor<'a, C, E> {
+    fn new(ef: &'a EfVec<C, E>) -> Self {
+        let mut iter = ef.iter();
+        let cur = iter.next();
+        Self { ef, iter, cur }
+    }
+
+    /// Move to the first value larger than `x`.
+    fn skip_past(&mut self, x: u64) {
+        while self.cur == Some(x) {
+            self.cur = self.iter.next();
+        }
+    }
+
+    /// Move to the first value `>= x`.
+    ///
+    /// Nearby values are found by scanning the already decoded chunk. Values
+    /// further away are found by a binary search over the first value of each
+    /// chunk, skipping the chunks in between.
+    fn seek(&mut self, x: u64) {
+        for _ in 0..C::L {
+            match self.cur {
+                Some(v) if v < x => self.cur = self.iter.next(),
+                _ => return,
+            }
+        }
+        if self.cur.is_none_or(|v| v >= x) {
+            return;
+        }
+        self.cur = match self.ef.next_geq(x) {
+            Some((pos, v)) => {
+                let front = self.ef.len() - self.iter.len();
+                self.iter.nth(pos - front);
+                Some(v)
+            }
+            None => {
+                self.iter.nth(self.iter.len());
+                None
+            }
+        };
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> EfVec<C, E> {
+    /// Iterate over the distinct values in both `self` and `other`.
+    pub fn intersect<'a, E2: AsRef<[C]>>(
+        &'a self,
+        other: &'a EfVec<C, E2>,
+    ) -> Intersect<'a, C, E, E2> {
+        Intersect {
+            a: Cursor::new(self),
+            b: Cursor::new(other),
+        }
+    }
+
+    /// Iterate over the distinct values in `self` or `other`.
+    pub fn union<'a, E2: AsRef<[C]>>(&'a self, other: &'a EfVec<C, E2>) -> Union<'a, C, E, E2> {
+        Union {
+            a: Cursor::new(self),
+            b: Cursor::new(other),
+        }
+    }
+
+    /// Iterate over the distinct values in `self` that are not in `other`.
+    pub fn difference<'a, E2: AsRef<[C]>>(
+        &'a self,
+        other: &'a EfVec<C, E2>,
+    ) -> Difference<'a, C, E, E2> {
+        Difference {
+            a: Cursor::new(self),
+            b: Cursor::new(other),
+        }
+    }
+
+    /// Iterate over the distinct values in all `parts`.
+    pub fn intersect_all<'a>(parts: &[&'a Self]) -> IntersectAll<'a, C, E> {
+        IntersectAll {
+            cursors: parts.iter().map(|ef| Cursor::new(*ef)).collect(),
+        }
+    }
+
+    /// Iterate over the distinct values in any of the `parts`.
+    pub fn union_all<'a>(parts: &[&'a Self]) -> UnionAll<'a, C, E> {
+        let cursors: Vec<_> = parts.iter().map(|ef| Cursor::new(*ef)).collect();
+        let heap = cursors
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| Some(Reverse((c.cur?, i))))
+            .collect();
+        UnionAll { cursors, heap }
+    }
+}
+
+/// Iterator over the intersection of two vectors, returned by [`EfVec::intersect`].
+pub struct Intersect<'a, C, E, E2> {
+    a: Cursor<'a, C, E>,
+    b: Cursor<'a, C, E2>,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>, E2: AsRef<[C]>> Iterator for Intersect<'_, C, E, E2> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            let (x, y) = (self.a.cur?, self.b.cur?);
+            if x < y {
+                self.a.seek(y);
+            } else if y < x {
+                self.b.seek(x);
+            } else {
+                self.a.skip_past(x);
+                self.b.skip_past(x);
+                return Some(x);
+            }
+        }
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>, E2: AsRef<[C]>> FusedIterator for Intersect<'_, C, E, E2> {}
+
+/// Iterator over the union of two vectors, returned by [`EfVec::union`].
+pub struct Union<'a, C, E, E2> {
+    a: Cursor<'a, C, E>,
+    b: Cursor<'a, C, E2>,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>, E2: AsRef<[C]>> Iterator for Union<'_, C, E, E2> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let x = match (self.a.cur, self.b.cur) {
+            (Some(x), Some(y)) => x.min(y),
+            (x, y) => x.or(y)?,
+        };
+        self.a.skip_past(x);
+        self.b.skip_past(x);
+        Some(x)
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>, E2: AsRef<[C]>> FusedIterator for Union<'_, C, E, E2> {}
+
+/// Iterator over the difference of two vectors, returned by [`EfVec::difference`].
+pub struct Difference<'a, C, E, E2> {
+    a: Cursor<'a, C, E>,
+    b: Cursor<'a, C, E2>,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>, E2: AsRef<[C]>> Iterator for Difference<'_, C, E, E2> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            let x = self.a.cur?;
+            self.a.skip_past(x);
+            self.b.seek(x);
+            if self.b.cur != Some(x) {
+                return Some(x);
+            }
+        }
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>, E2: AsRef<[C]>> FusedIterator for Difference<'_, C, E, E2> {}
+
+/// Iterator over the intersection of any number of vectors, returned by
+/// [`EfVec::intersect_all`].
+pub struct IntersectAll<'a, C, E> {
+    cursors: Vec<Cursor<'a, C, E>>,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> Iterator for IntersectAll<'_, C, E> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        // The intersection of no sets is empty.
+        let mut x = self.cursors.first()?.cur?;
+        'candidate: loop {
+            for c in &mut self.cursors {
+                c.seek(x);
+                let v = c.cur?;
+                if v > x {
+                    x = v;
+                    continue 'candidate;
+                }
+            }
+            for c in &mut self.cursors {
+                c.skip_past(x);
+            }
+            return Some(x);
+        }
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> FusedIterator for IntersectAll<'_, C, E> {}
+
+/// Iterator over the union of any number of vectors, returned by
+/// [`EfVec::union_all`].
+pub struct UnionAll<'a, C, E> {
+    cursors: Vec<Cursor<'a, C, E>>,
+    /// The current value and index of each cursor that is not at the end.
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> Iterator for UnionAll<'_, C, E> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let Reverse((x, _)) = *self.heap.peek()?;
+        while let Some(&Reverse((v, i))) = self.heap.peek() {
+            if v != x {
+                break;
+            }
+            self.heap.pop();
+            let c = &mut self.cursors[i];
+            c.skip_past(x);
+            if let Some(v) = c.cur {
+                self.heap.push(Reverse((v, i)));
+            }
+        }
+        Some(x)
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> FusedIterator for UnionAll<'_, C, E> {}
+
+#[test]
+fn set_ops() {
+    use std::collections::BTreeSet;
+
+    let lists = [
+        crate::random_vals(10000, 0, 10),
+        crate::random_vals(10000, 0, 20),
+        crate::random_vals(1000, 0, 100),
+        crate::random_vals(100, 0, 10000),
+        vec![],
+    ];
+    let efs: Vec<_> = lists
+        .iter()
+        .map(|l| crate::CachelineEfVec::new(l))
+        .collect();
+    let sets: Vec<BTreeSet<u64>> = lists.iter().map(|l| l.iter().copied().collect()).collect();
+    for i in 0..lists.len() {
+        for j in 0..lists.len() {
+            let (a, b) = (&efs[i], &efs[j]);
+            assert!(a.intersect(b).eq(sets[i].intersection(&sets[j]).copied()));
+            assert!(a.union(b).eq(sets[i].union(&sets[j]).copied()));
+            assert!(a.difference(b).eq(sets[i].difference(&sets[j]).copied()));
+        }
+    }
+
+    let all: Vec<_> = efs.iter().collect();
+    for k in 0..=4 {
+        let expected: BTreeSet<u64> = sets[..k]
+            .iter()
+            .skip(1)
+            .fold(sets.first().cloned().unwrap_or_default(), |acc, s| {
+                acc.intersection(s).copied().collect()
+            });
+        let expected = if k == 0 { BTreeSet::new() } else { expected };
+        assert!(crate::CachelineEfVec::intersect_all(&all[..k]).eq(expected));
+        let expected: BTreeSet<u64> = sets[..k].iter().flatten().copied().collect();
+        assert!(crate::CachelineEfVec::union_all(&all[..k]).eq(expected));
+    }
+
+    // Results can be encoded again.
+    let ef: crate::CachelineEfVec = efs[0].intersect(&efs[1]).collect();
+    assert!(ef.iter().eq(sets[0].intersection(&sets[1]).copied()));
+}
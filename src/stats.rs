@@ -0,0 +1,157 @@
+use crate::{escaped, first, EfChunk, EfVec};
+use std::fmt;
+
+/// Statistics about the encoding of an [`EfVec`], returned by [`EfVec::stats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EfStats {
+    /// The number of values.
+    pub len: usize,
+    /// The number of chunks, excluding overflow cachelines.
+    pub chunks: usize,
+    /// The number of escaped chunks.
+    pub escaped_chunks: usize,
+    /// The number of overflow cachelines holding the values of escaped chunks.
+    pub overflow_cachelines: usize,
+    /// The total size in bytes.
+    pub bytes: usize,
+    /// The number of encoded (not escaped) chunks whose range of
+    /// values is in `[i/10, (i+1)/10)` of [`EfChunk::MAX_RANGE`], where the
+    /// last bucket also contains chunks using the full range.
+    pub range_histogram: [usize; 10],
+    /// The smallest difference between [`EfChunk::MAX_RANGE`] and the range
+    /// of an encoded chunk, or `None` when there are no encoded chunks.
+    /// Values are escaped when this would become negative.
+    pub min_headroom: Option<u64>,
+    /// The total number of unused bits after the last 1-bit of the unary
+    /// high parts (e.g. `high_boundaries` of [`CachelineEf`](crate::CachelineEf))
+    /// of all encoded chunks; see [`EfChunk::unused_high_bits`].
+    pub unused_high_bits: usize,
+    /// The smallest number of unused high bits of an encoded chunk, or `None`
+    /// when there are no encoded chunks.
+    pub min_unused_high_bits: Option<usize>,
+    /// The number of unused value slots in the last chunk.
+    pub padding: usize,
+}
+
+impl EfStats {
+    /// The number of bits per value, or 0 for an empty vector.
+    pub fn bits_per_value(&self) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        (8 * self.bytes) as f64 / self.len as f64
+    }
+}
+
+impl fmt::Display for EfStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} values in {} chunks ({} escaped, {} overflow cachelines)",
+            self.len, self.chunks, self.escaped_chunks, self.overflow_cachelines
+        )?;
+        writeln!(
+            f,
+            "{} bytes, {:.2} bits/value, {} unused slots in the last chunk",
+            self.bytes,
+            self.bits_per_value(),
+            self.padding
+        )?;
+        match self.min_headroom {
+            Some(h) => writeln!(f, "minimum range headroom: {h}")?,
+            None => writeln!(f, "minimum range headroom: -")?,
+        }
+        match self.min_unused_high_bits {
+            Some(b) => writeln!(
+                f,
+                "unused high bits: {} in total, at least {b} per chunk",
+                self.unused_high_bits
+            )?,
+            None => writeln!(f, "unused high bits: -")?,
+        }
+        writeln!(f, "range of chunks, relative to the maximum:")?;
+        for (i, &n) in self.range_histogram.iter().enumerate() {
+            writeln!(f, "{:>3}%-{:>3}%: {n}", 10 * i, 10 * (i + 1))?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> EfVec<C, E> {
+    /// Compute statistics about the encoding, e.g. to find out how close
+    /// chunks are to their maximum range.
+    pub fn stats(&self) -> EfStats {
+        let chunks = self.chunks();
+        let mut escaped_chunks = 0;
+        let mut range_histogram = [0; 10];
+        let mut min_headroom = None;
+        let mut unused_high_bits = 0;
+        let mut min_unused_high_bits = None;
+        for (k, c) in chunks.iter().enumerate() {
+            if escaped(c).is_some() {
+                escaped_chunks += 1;
+                continue;
+            }
+            let range = self.chunk_get(k, self.chunk_len(k) - 1) - first(c);
+            let bucket = (10 * range as u128 / C::MAX_RANGE as u128).min(9) as usize;
+            range_histogram[bucket] += 1;
+            let headroom = C::MAX_RANGE.saturating_sub(range);
+            min_headroom = Some(min_headroom.map_or(headroom, |h: u64| h.min(headroom)));
+            let unused = c.unused_high_bits();
+            unused_high_bits += unused;
+            min_unused_high_bits =
+                Some(min_unused_high_bits.map_or(unused, |u: usize| u.min(unused)));
+        }
+        EfStats {
+            len: self.len,
+            chunks: chunks.len(),
+            escaped_chunks,
+            overflow_cachelines: self.ef.as_ref().len() - chunks.len(),
+            bytes: self.size_in_bytes(),
+            range_histogram,
+            min_headroom,
+            unused_high_bits,
+            min_unused_high_bits,
+            padding: chunks.len() * C::L - self.len,
+        }
+    }
+}
+
+#[test]
+fn stats() {
+    let vals = crate::test_vals(10000, 1000, 100);
+    let ef = crate::CachelineEfVec::new(&vals);
+    let stats = ef.stats();
+    assert_eq!(stats.len, vals.len());
+    assert_eq!(stats.chunks, vals.len().div_ceil(44));
+    assert!(stats.escaped_chunks > 0);
+    assert_eq!(stats.bytes, 64 * (stats.chunks + stats.overflow_cachelines));
+    assert_eq!(
+        stats.range_histogram.iter().sum::<usize>(),
+        stats.chunks - stats.escaped_chunks
+    );
+    assert_eq!(stats.padding, 44 * stats.chunks - vals.len());
+    assert!(stats.min_headroom.unwrap() < crate::CachelineEf::MAX_RANGE);
+    assert!(stats.to_string().contains("escaped"));
+
+    // A chunk using exactly the full range has no headroom.
+    let max = crate::CachelineEf::MAX_RANGE;
+    let stats = crate::CachelineEfVec::new(&[5, max + 5]).stats();
+    assert_eq!(stats.min_headroom, Some(0));
+    // The high parts 0 and 84 use bits 0 and 85 of the 128 high bits.
+    assert_eq!(stats.unused_high_bits, 128 - 86);
+    assert_eq!(stats.min_unused_high_bits, Some(128 - 86));
+    assert_eq!(stats.range_histogram[9], 1);
+    assert_eq!(stats.padding, 42);
+    let stats = crate::CachelineEfVec::new(&[]).stats();
+    assert_eq!(stats.min_headroom, None);
+    assert_eq!(stats.min_unused_high_bits, None);
+    assert_eq!(stats.bits_per_value(), 0.0);
+
+    // 44 values with the same high part use only the first 44 high bits.
+    let vals: Vec<u64> = (0..44).collect();
+    let stats = crate::CachelineEfVec::new(&vals).stats();
+    assert_eq!(stats.min_unused_high_bits, Some(128 - 44));
+    let stats = crate::PackedEfVec::<8>::new(&vals[..43]).stats();
+    assert_eq!(stats.unused_high_bits, 480 - 43 * 8 - 43);
+}
@@ -0,0 +1,241 @@
+use crate::{first, CachelineEf, CachelineEfError, EfChunk, EfVec, EfVecBuilder, Iter};
+use std::iter::FusedIterator;
+
+/// A strictly increasing list of values, stored as [`CachelineEf`] chunks.
+pub type CachelineStrictEfVec<E = Vec<CachelineEf>> = StrictEfVec<CachelineEf, E>;
+
+/// A strictly increasing list of values `v`, stored as the non-decreasing
+/// list `v[i] - i` in an [`EfVec`].
+///
+/// This reduces the range of each chunk by `L - 1`, so that slightly sparser
+/// data can be encoded without escaping chunks. E.g. [`CachelineEf`] chunks
+/// can then hold values that are on average `21547/43 = 501` apart, instead of `500`.
+#[derive(Clone, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+pub struct StrictEfVec<C, E = Vec<C>> {
+    shifted: EfVec<C, E>,
+}
+
+impl<C: EfChunk> StrictEfVec<C> {
+    /// Encode a strictly increasing list of values.
+    ///
+    /// Panics when the values are not strictly increasing; see [`Self::try_new`].
+    pub fn new(vals: &[u64]) -> Self {
+        Self::try_new(vals).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Encode a strictly increasing list of values, or return an error
+    /// explaining where the values are not strictly increasing.
+    pub fn try_new(vals: &[u64]) -> Result<Self, CachelineEfError> {
+        for (i, w) in vals.windows(2).enumerate() {
+            if w[0] >= w[1] {
+                return Err(CachelineEfError::NotStrictlyIncreasing {
+                    chunk: (i + 1) / C::L,
+                    index: i + 1,
+                    prev: w[0],
+                    value: w[1],
+                });
+            }
+        }
+        let mut shifted = EfVecBuilder::with_capacity(vals.len());
+        for (i, &v) in vals.iter().enumerate() {
+            shifted.push(v - i as u64);
+        }
+        Ok(Self {
+            shifted: shifted.finish(),
+        })
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> StrictEfVec<C, E> {
+    pub fn index(&self, index: usize) -> u64 {
+        self.shifted.index(index) + index as u64
+    }
+    pub fn len(&self) -> usize {
+        self.shifted.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.shifted.is_empty()
+    }
+    pub fn size_in_bytes(&self) -> usize {
+        self.shifted.size_in_bytes()
+    }
+
+    /// The stored values `v[i] - i`.
+    pub fn shifted(&self) -> &EfVec<C, E> {
+        &self.shifted
+    }
+
+    /// Iterate over all values.
+    pub fn iter(&self) -> StrictIter<'_, C, E> {
+        StrictIter {
+            shifted: self.shifted.iter(),
+            front: 0,
+            back: self.len() as u64,
+        }
+    }
+
+    /// Return the position and value of the first value `>= x`, or `None`
+    /// when all values are smaller than `x`.
+    pub fn next_geq(&self, x: u64) -> Option<(usize, u64)> {
+        let pos = self.rank(x);
+        (pos < self.len()).then(|| (pos, self.index(pos)))
+    }
+
+    /// Return the position and value of the last value `<= x`, or `None`
+    /// when all values are larger than `x`.
+    pub fn prev_leq(&self, x: u64) -> Option<(usize, u64)> {
+        let pos = x.checked_add(1).map_or(self.len(), |x| self.rank(x));
+        let pos = pos.checked_sub(1)?;
+        Some((pos, self.index(pos)))
+    }
+
+    /// The number of values strictly less than `x`.
+    ///
+    /// The chunk is found by a binary search over the first value of each
+    /// chunk, followed by a binary search within a single cacheline.
+    pub fn rank(&self, x: u64) -> usize {
+        // `v[i] = shifted[i] + i`, so the first value of chunk `k` is its
+        // first stored value plus `k * L`.
+        let chunks = self.shifted.chunks();
+        let (mut lo, mut hi) = (0, chunks.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if first(&chunks[mid]) + ((mid * C::L) as u64) < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        if lo == 0 {
+            return 0;
+        }
+        let k = lo - 1;
+        let start = k * C::L;
+        let (mut lo, mut hi) = (0, self.shifted.chunk_len(k));
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.shifted.chunk_get(k, mid) + ((start + mid) as u64) < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        start + lo
+    }
+
+    /// Check that the data is a valid encoding; see [`EfVec::validate`].
+    /// Any valid encoding of the shifted values is strictly increasing.
+    pub fn validate(&self) -> Result<(), CachelineEfError> {
+        self.shifted.validate()
+    }
+}
+
+impl<'a, C: EfChunk, E: AsRef<[C]>> IntoIterator for &'a StrictEfVec<C, E> {
+    type Item = u64;
+    type IntoIter = StrictIter<'a, C, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`StrictEfVec`], adding back the index to
+/// each value of [`StrictEfVec::shifted`].
+pub struct StrictIter<'a, C, E> {
+    shifted: Iter<'a, C, E>,
+    /// Index of the next value returned by `next`.
+    front: u64,
+    /// One past the index of the next value returned by `next_back`.
+    back: u64,
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> Iterator for StrictIter<'_, C, E> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let v = self.shifted.next()? + self.front;
+        self.front += 1;
+        Some(v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.shifted.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<u64> {
+        self.front += (n as u64).min(self.back - self.front);
+        let v = self.shifted.nth(n)? + self.front;
+        self.front += 1;
+        Some(v)
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> DoubleEndedIterator for StrictIter<'_, C, E> {
+    fn next_back(&mut self) -> Option<u64> {
+        let v = self.shifted.next_back()?;
+        self.back -= 1;
+        Some(v + self.back)
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> ExactSizeIterator for StrictIter<'_, C, E> {}
+
+impl<C: EfChunk, E: AsRef<[C]>> FusedIterator for StrictIter<'_, C, E> {}
+
+#[test]
+fn strict() {
+    for max_gap in [2, 100, 1002, 100000] {
+        // Adding `i + 1` makes the gaps at least 1.
+        let vals: Vec<u64> = crate::random_vals(10000, 0, max_gap)
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| v + i as u64 + 1)
+            .collect();
+        let v = vals[vals.len() - 1];
+        let ef = CachelineStrictEfVec::new(&vals);
+        assert_eq!(ef.len(), vals.len());
+        assert!(ef.iter().eq(vals.iter().copied()));
+        assert!((&ef).into_iter().rev().eq(vals.iter().copied().rev()));
+        assert_eq!(ef.iter().len(), vals.len());
+        let mut it = ef.iter();
+        assert_eq!(it.nth(100), Some(vals[100]));
+        assert_eq!(it.next_back(), Some(v));
+        assert_eq!(it.next(), Some(vals[101]));
+        assert_eq!(it.len(), vals.len() - 103);
+        for (i, &v) in vals.iter().enumerate() {
+            assert_eq!(ef.index(i), v);
+            assert_eq!(ef.rank(v), i);
+            assert_eq!(ef.rank(v + 1), i + 1);
+            assert_eq!(ef.next_geq(v), Some((i, v)));
+            assert_eq!(ef.prev_leq(v), Some((i, v)));
+            if i > 0 && vals[i - 1] + 1 < v {
+                assert_eq!(ef.next_geq(v - 1), Some((i, v)));
+                assert_eq!(ef.prev_leq(v - 1), Some((i - 1, vals[i - 1])));
+            }
+        }
+        assert_eq!(ef.next_geq(v + 1), None);
+        assert_eq!(ef.prev_leq(u64::MAX), Some((vals.len() - 1, v)));
+        assert_eq!(ef.validate(), Ok(()));
+    }
+
+    // Gaps of 501 span a range of 21543 per chunk, which only fits after
+    // subtracting the index.
+    let vals: Vec<u64> = (1..=1000).map(|i| 501 * i).collect();
+    assert!(crate::CachelineEfVec::new(&vals).stats().escaped_chunks > 0);
+    let ef = CachelineStrictEfVec::new(&vals);
+    assert_eq!(ef.shifted().stats().escaped_chunks, 0);
+    assert_eq!(ef.prev_leq(0), None);
+    assert_eq!(ef.next_geq(0), Some((0, 501)));
+
+    assert_eq!(
+        CachelineStrictEfVec::try_new(&[1, 2, 2]).err(),
+        Some(CachelineEfError::NotStrictlyIncreasing {
+            chunk: 0,
+            index: 2,
+            prev: 2,
+            value: 2
+        })
+    );
+    assert!(CachelineStrictEfVec::new(&[]).next_geq(0).is_none());
+}
@@ -0,0 +1,232 @@
+use crate::{CachelineEf, CachelineEfError, EfChunk, EfOffsets, EfOffsetsBuilder, OffsetsIter};
+use std::iter::FusedIterator;
+
+/// Byte strings whose offsets are stored as [`CachelineEf`] chunks.
+pub type CachelineEfStrings<B = Vec<u8>, E = Vec<CachelineEf>> = EfStrings<CachelineEf, B, E>;
+
+/// Builder for [`CachelineEfStrings`].
+pub type CachelineEfStringsBuilder = EfStringsBuilder<CachelineEf>;
+
+/// A list of byte strings, stored as their concatenated bytes together with
+/// the [`EfOffsets`] of each string.
+///
+/// Compared to a `Vec<u64>` of offsets, this uses around 12 instead of 64 bits
+/// per string for strings of around 100 bytes, and [`Self::get`] still reads
+/// a single cacheline of offsets.
+#[derive(Clone, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+pub struct EfStrings<C, B = Vec<u8>, E = Vec<C>> {
+    bytes: B,
+    offsets: EfOffsets<C, E>,
+}
+
+impl<C: EfChunk> EfStrings<C> {
+    /// Store the given strings.
+    pub fn new<S: AsRef<[u8]>>(strings: &[S]) -> Self {
+        strings.iter().collect()
+    }
+}
+
+impl<C: EfChunk, B: AsRef<[u8]>, E: AsRef<[C]>> EfStrings<C, B, E> {
+    /// The number of strings.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+    pub fn size_in_bytes(&self) -> usize {
+        self.bytes.as_ref().len() + self.offsets.size_in_bytes()
+    }
+
+    /// The concatenated bytes of all strings.
+    pub fn bytes(&self) -> &[u8] {
+        self.bytes.as_ref()
+    }
+
+    /// The offsets of the strings in [`Self::bytes`].
+    pub fn offsets(&self) -> &EfOffsets<C, E> {
+        &self.offsets
+    }
+
+    /// The bytes of string `i`.
+    pub fn get(&self, i: usize) -> &[u8] {
+        let range = self.offsets.range(i);
+        &self.bytes.as_ref()[range.start as usize..range.end as usize]
+    }
+
+    /// Iterate over all strings, reading the offsets sequentially.
+    pub fn iter(&self) -> StringsIter<'_, C, E> {
+        StringsIter {
+            bytes: self.bytes.as_ref(),
+            offsets: self.offsets.iter(),
+        }
+    }
+
+    /// Check that the offsets are valid and within the bytes; see [`EfVec::validate`](crate::EfVec::validate).
+    pub fn validate(&self) -> Result<(), CachelineEfError> {
+        self.offsets.validate()?;
+        if self.is_empty() {
+            return Ok(());
+        }
+        let end = self.offsets.range(self.len() - 1).end;
+        let bytes = self.bytes.as_ref().len() as u64;
+        if end > bytes {
+            return Err(CachelineEfError::OffsetOutOfBounds { end, bytes });
+        }
+        Ok(())
+    }
+}
+
+impl<'a, C: EfChunk, B: AsRef<[u8]>, E: AsRef<[C]>> IntoIterator for &'a EfStrings<C, B, E> {
+    type Item = &'a [u8];
+    type IntoIter = StringsIter<'a, C, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the strings of an [`EfStrings`], returned by [`EfStrings::iter`].
+pub struct StringsIter<'a, C, E> {
+    bytes: &'a [u8],
+    offsets: OffsetsIter<'a, C, E>,
+}
+
+impl<'a, C: EfChunk, E: AsRef<[C]>> Iterator for StringsIter<'a, C, E> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let r = self.offsets.next()?;
+        Some(&self.bytes[r.start as usize..r.end as usize])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.offsets.size_hint()
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> DoubleEndedIterator for StringsIter<'_, C, E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let r = self.offsets.next_back()?;
+        Some(&self.bytes[r.start as usize..r.end as usize])
+    }
+}
+
+impl<C: EfChunk, E: AsRef<[C]>> ExactSizeIterator for StringsIter<'_, C, E> {}
+
+impl<C: EfChunk, E: AsRef<[C]>> FusedIterator for StringsIter<'_, C, E> {}
+
+/// Collects strings into an [`EfStrings`], one at a time.
+pub struct EfStringsBuilder<C> {
+    bytes: Vec<u8>,
+    offsets: EfOffsetsBuilder<C>,
+}
+
+impl<C: EfChunk> Default for EfStringsBuilder<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: EfChunk> EfStringsBuilder<C> {
+    pub fn new() -> Self {
+        Self::with_capacity(0, 0)
+    }
+
+    /// Create a builder with space for `n` strings of `bytes` bytes in total.
+    pub fn with_capacity(n: usize, bytes: usize) -> Self {
+        let mut offsets = EfOffsetsBuilder::with_capacity(n + 1);
+        offsets.push(0);
+        Self {
+            bytes: Vec::with_capacity(bytes),
+            offsets,
+        }
+    }
+
+    /// The number of strings pushed so far.
+    pub fn len(&self) -> usize {
+        self.offsets.num_records()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append a string.
+    pub fn push(&mut self, s: impl AsRef<[u8]>) {
+        self.bytes.extend_from_slice(s.as_ref());
+        self.offsets.push(self.bytes.len() as u64);
+    }
+
+    /// Encode the offsets of all pushed strings.
+    pub fn finish(self) -> EfStrings<C> {
+        EfStrings {
+            offsets: self.offsets.finish(),
+            bytes: self.bytes,
+        }
+    }
+}
+
+impl<C: EfChunk, S: AsRef<[u8]>> Extend<S> for EfStringsBuilder<C> {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        for s in iter {
+            self.push(s);
+        }
+    }
+}
+
+impl<C: EfChunk, S: AsRef<[u8]>> FromIterator<S> for EfStrings<C> {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut builder = EfStringsBuilder::new();
+        builder.extend(iter);
+        builder.finish()
+    }
+}
+
+#[test]
+fn strings() {
+    let strings: Vec<String> = (0..10000)
+        .map(|i| "x".repeat(rand::random::<usize>() % 200) + &i.to_string())
+        .collect();
+    let ef = CachelineEfStrings::new(&strings);
+    assert_eq!(ef.len(), strings.len());
+    assert_eq!(ef.validate(), Ok(()));
+    for (i, s) in strings.iter().enumerate() {
+        assert_eq!(ef.get(i), s.as_bytes());
+    }
+    assert!(ef.iter().eq(strings.iter().map(|s| s.as_bytes())));
+    assert!((&ef)
+        .into_iter()
+        .rev()
+        .eq(strings.iter().rev().map(|s| s.as_bytes())));
+    assert_eq!(ef.iter().len(), strings.len());
+    // Offsets take around 12 bits per string.
+    let offset_bits = (8 * ef.offsets().size_in_bytes()) as f64 / strings.len() as f64;
+    assert!(offset_bits < 13.0, "{offset_bits}");
+    assert_eq!(
+        ef.size_in_bytes(),
+        ef.bytes().len() + ef.offsets().size_in_bytes()
+    );
+
+    let mut builder = CachelineEfStringsBuilder::new();
+    builder.push("");
+    builder.push(b"ab");
+    builder.push(vec![0u8, 1]);
+    assert_eq!(builder.len(), 3);
+    let ef = builder.finish();
+    assert_eq!(ef.get(0), b"");
+    assert_eq!(ef.get(1), b"ab");
+    assert_eq!(ef.get(2), [0, 1]);
+    assert!(CachelineEfStrings::new::<&str>(&[]).is_empty());
+
+    // Offsets past the end of the bytes are rejected.
+    let truncated = EfStrings {
+        bytes: &b"abc"[..2],
+        offsets: crate::CachelineEfOffsets::new(&[0, 1, 3]),
+    };
+    assert_eq!(
+        truncated.validate(),
+        Err(CachelineEfError::OffsetOutOfBounds { end: 3, bytes: 2 })
+    );
+}
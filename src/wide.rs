@@ -0,0 +1,134 @@
+use crate::{check_chunk, CachelineEfError, EfChunk, EfVec, EfView};
+
+/// Number of stored values per unit.
+const L: usize = 40;
+
+/// A list of arbitrary `u64` values encoded in [`CachelineEfWide`] chunks.
+pub type CachelineEfWideVec<E = Vec<CachelineEfWide>> = EfVec<CachelineEfWide, E>;
+
+/// Single-cacheline Elias-Fano encoding that holds 40 64-bit values in a range of size 256*88=22528.
+///
+/// This is the same as [`CachelineEf`](crate::CachelineEf), but stores the
+/// offset of the first value in 64 instead of 32 bits, at the cost of 4 values
+/// per cacheline, i.e. 12.8 instead of 11.6 bits per value.
+// Like `CachelineEf`, this has size 64 bytes and is aligned to 64 bytes.
+#[derive(Clone, Copy, mem_dbg::MemSize, mem_dbg::MemDbg)]
+#[repr(C)]
+#[repr(align(64))]
+#[cfg_attr(feature = "epserde", derive(epserde::prelude::Epserde))]
+#[cfg_attr(feature = "epserde", zero_copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "crate::serialize::ChunkRepr<u64>",
+        into = "crate::serialize::ChunkRepr<u64>"
+    )
+)]
+#[copy_type]
+pub struct CachelineEfWide {
+    // 128 bits with 40 1-bits, as in `CachelineEf`.
+    pub(crate) high_boundaries: [u64; 2],
+    // The offset of the first element, divided by 256.
+    pub(crate) reduced_offset: u64,
+    // Last 8 bits of each number.
+    pub(crate) low_bits: [u8; L],
+}
+
+impl CachelineEfWide {
+    fn view(&self) -> EfView<'_, 2> {
+        EfView {
+            high_boundaries: &self.high_boundaries,
+            reduced_offset: self.reduced_offset,
+            low_bits: &self.low_bits,
+        }
+    }
+}
+
+// SAFETY: `CachelineEfWide` is 8 words without padding, and bit 0 of
+// `high_boundaries` is set for the first value.
+unsafe impl EfChunk for CachelineEfWide {
+    const L: usize = L;
+    const MAX_RANGE: u64 = 256 * (128 - L as u64);
+    const TAG: u64 = 2 << 56;
+
+    fn try_new(vals: &[u64], chunk: usize) -> Result<Self, CachelineEfError> {
+        check_chunk::<Self>(vals, chunk)?;
+        let l = vals.len();
+        if vals[l - 1] - vals[0] > Self::MAX_RANGE {
+            return Err(CachelineEfError::RangeTooLarge {
+                chunk,
+                first: vals[0],
+                last: vals[l - 1],
+                max_range: Self::MAX_RANGE,
+            });
+        }
+
+        let mut high_boundaries = [0u64; 2];
+        let mut low_bits = [0u8; L];
+        let reduced_offset = EfView::encode(vals, &mut high_boundaries, &mut low_bits);
+        Ok(Self {
+            high_boundaries,
+            reduced_offset,
+            low_bits,
+        })
+    }
+
+    fn get(&self, idx: usize) -> u64 {
+        self.view().get(idx)
+    }
+    fn len(&self) -> usize {
+        self.view().len()
+    }
+    fn first(&self) -> u64 {
+        self.view().first()
+    }
+    fn rank(&self, x: u64) -> usize {
+        self.view().rank(x)
+    }
+    fn unused_high_bits(&self) -> usize {
+        self.view().unused_high_bits()
+    }
+    fn validate(&self, len: usize, chunk: usize) -> Result<(), CachelineEfError> {
+        self.view().validate(len, chunk)
+    }
+    fn decode(&self, out: &mut [u64]) {
+        self.view().decode(out)
+    }
+}
+
+#[test]
+fn wide() {
+    let max = (128 - L as u64) * 256;
+    for offset in [0, 1 << 48, u64::MAX - max] {
+        let mut vals = [0u64; L];
+        for _ in 0..10000 {
+            for v in &mut vals {
+                *v = offset + rand::random::<u64>() % max;
+            }
+            vals.sort_unstable();
+
+            let lef = CachelineEfWide::try_new(&vals, 0).unwrap();
+            for i in 0..L {
+                assert_eq!(lef.get(i), vals[i], "error; full list: {:?}", vals);
+            }
+        }
+    }
+
+    // 48-bit offsets with a gap of around 100 never need to be escaped.
+    let mut vals = vec![];
+    let mut v = (1 << 48) - 500_000;
+    for _ in 0..10000 {
+        v += rand::random::<u64>() % 200;
+        vals.push(v);
+    }
+    let ef = CachelineEfWideVec::new(&vals);
+    assert_eq!(ef.size_in_bytes(), 64 * vals.len().div_ceil(L));
+    assert!(ef.iter().eq(vals.iter().copied()));
+    for _ in 0..10000 {
+        let x = vals[rand::random::<usize>() % vals.len()] + rand::random::<u64>() % 3 - 1;
+        let rank = vals.partition_point(|&v| v < x);
+        assert_eq!(ef.rank(x), rank, "x = {x}");
+        assert_eq!(ef.next_geq(x), vals.get(rank).map(|&v| (rank, v)));
+    }
+}